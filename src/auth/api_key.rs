@@ -0,0 +1,321 @@
+use actix_web::{dev::ServiceRequest, web, HttpMessage};
+use actix_web_httpauth::extractors::bearer::BearerAuth;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::error as TracingError;
+
+use crate::{
+    auth::jwt::merchant_jwt_validator,
+    db::migrations::api_keys::{
+        inserts_and_updates::{
+            ADD_API_KEY, REVOKE_API_KEY, TOUCH_PUBLISHABLE_KEY, TOUCH_SECRET_KEY,
+        },
+        select_queries::{
+            COUNT_ACTIVE_MERCHANT_API_KEYS, GET_ACTIVE_API_KEY_BY_LOOKUP_ID, GET_MERCHANT_API_KEYS,
+        },
+    },
+    error::StabuseError,
+    types::types::{
+        AccountMode, ApiKey, ApiKeyCredentials, ApiKeyIdentity, ApiKeyKind, Claims, CreatedApiKey,
+    },
+    utils::secret::generate_secret,
+};
+
+pub const PAYMENTS_CREATE_SCOPE: &str = "payments:create";
+pub const PAYMENTS_READ_SCOPE: &str = "payments:read";
+const API_KEY_SCOPES: [&str; 2] = [PAYMENTS_CREATE_SCOPE, PAYMENTS_READ_SCOPE];
+
+const PUBLISHABLE_KEY_PREFIX: &str = "pk_";
+const SECRET_KEY_PREFIX: &str = "sk_";
+const MAX_API_KEYS_PER_MERCHANT: i64 = 10;
+const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+/// Keys look like `pk_<mode>_<lookup id>_<secret>`. The lookup id is hex so
+/// it can never contain the separator, and is shared by both keys of a pair.
+fn generate_lookup_id() -> String {
+    let mut id = [0u8; 4];
+    rand::thread_rng().fill_bytes(&mut id);
+    hex::encode(id)
+}
+
+/// Keys carry 256 bits of randomness, so a plain SHA-256 is enough to keep
+/// them useless if the table leaks, without paying for bcrypt per request.
+fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn parse_api_key(key: &str) -> Option<(ApiKeyKind, AccountMode, &str)> {
+    let (kind, rest) = if let Some(rest) = key.strip_prefix(PUBLISHABLE_KEY_PREFIX) {
+        (ApiKeyKind::Publishable, rest)
+    } else if let Some(rest) = key.strip_prefix(SECRET_KEY_PREFIX) {
+        (ApiKeyKind::Secret, rest)
+    } else {
+        return None;
+    };
+
+    let (mode, rest) = rest.split_once('_')?;
+    let mode = match mode {
+        "test" => AccountMode::Test,
+        "live" => AccountMode::Live,
+        _ => return None,
+    };
+
+    let (lookup_id, secret) = rest.split_once('_')?;
+    if lookup_id.is_empty() || secret.is_empty() {
+        return None;
+    }
+
+    Some((kind, mode, lookup_id))
+}
+
+fn validate_scopes(scopes: Option<Vec<String>>) -> Result<Vec<String>, StabuseError> {
+    let Some(requested) = scopes else {
+        return Ok(API_KEY_SCOPES
+            .iter()
+            .map(|scope| scope.to_string())
+            .collect());
+    };
+
+    let mut scopes: Vec<String> = vec![];
+    for scope in requested {
+        if !API_KEY_SCOPES.contains(&scope.as_str()) {
+            return Err(StabuseError::InvalidData(format!(
+                "Unknown API key scope: {}",
+                scope
+            )));
+        }
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    if scopes.is_empty() {
+        return Err(StabuseError::InvalidData(
+            "An API key needs at least one scope".to_string(),
+        ));
+    }
+
+    Ok(scopes)
+}
+
+/// Mints a publishable/secret key pair for one mode. The keys are only
+/// returned here; the database keeps their hashes.
+pub async fn create_api_key(
+    pool: &PgPool,
+    merchant_id: i32,
+    name: &str,
+    scopes: Option<Vec<String>>,
+    mode: AccountMode,
+) -> Result<CreatedApiKey, StabuseError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_API_KEY_NAME_LENGTH {
+        return Err(StabuseError::InvalidData(format!(
+            "API key name must be between 1 and {} characters",
+            MAX_API_KEY_NAME_LENGTH
+        )));
+    }
+    let scopes = validate_scopes(scopes)?;
+
+    let active: i64 = sqlx::query_scalar(COUNT_ACTIVE_MERCHANT_API_KEYS)
+        .bind(merchant_id)
+        .fetch_one(pool)
+        .await?;
+    if active >= MAX_API_KEYS_PER_MERCHANT {
+        return Err(StabuseError::InvalidData(format!(
+            "A merchant can have at most {} active API keys",
+            MAX_API_KEYS_PER_MERCHANT
+        )));
+    }
+
+    let lookup_id = generate_lookup_id();
+    let publishable_key = format!(
+        "{}{}_{}_{}",
+        PUBLISHABLE_KEY_PREFIX,
+        mode.as_str(),
+        lookup_id,
+        generate_secret()
+    );
+    let secret_key = format!(
+        "{}{}_{}_{}",
+        SECRET_KEY_PREFIX,
+        mode.as_str(),
+        lookup_id,
+        generate_secret()
+    );
+
+    let api_key = sqlx::query_as::<_, ApiKey>(ADD_API_KEY)
+        .bind(merchant_id)
+        .bind(name)
+        .bind(&lookup_id)
+        .bind(hash_api_key(&publishable_key))
+        .bind(hash_api_key(&secret_key))
+        .bind(&scopes)
+        .bind(mode)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(CreatedApiKey {
+        api_key,
+        publishable_key,
+        secret_key,
+    })
+}
+
+pub async fn get_api_keys(pool: &PgPool, merchant_id: i32) -> Result<Vec<ApiKey>, StabuseError> {
+    let api_keys = sqlx::query_as::<_, ApiKey>(GET_MERCHANT_API_KEYS)
+        .bind(merchant_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(api_keys)
+}
+
+/// Revokes both keys of a pair. Revoked pairs stay listed.
+pub async fn revoke_api_key(
+    pool: &PgPool,
+    merchant_id: i32,
+    key_id: i32,
+) -> Result<(), StabuseError> {
+    let revoked: Option<i32> = sqlx::query_scalar(REVOKE_API_KEY)
+        .bind(key_id)
+        .bind(merchant_id)
+        .fetch_optional(pool)
+        .await?;
+
+    if revoked.is_none() {
+        return Err(StabuseError::InvalidData(format!(
+            "No active API key with id {}",
+            key_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Resolves a key to its merchant, provided it is active and allowed
+/// `scope`. Publishable keys end up in browsers, so they are only ever
+/// good for creating payments, whatever scopes their pair has.
+pub async fn authenticate_api_key(
+    pool: &PgPool,
+    key: &str,
+    scope: &str,
+) -> Result<ApiKeyIdentity, StabuseError> {
+    let invalid = || StabuseError::Unauthorized("Invalid API key".to_string());
+
+    let (kind, mode, lookup_id) = parse_api_key(key).ok_or_else(invalid)?;
+    let credentials = sqlx::query_as::<_, ApiKeyCredentials>(GET_ACTIVE_API_KEY_BY_LOOKUP_ID)
+        .bind(lookup_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(invalid)?;
+
+    let expected_hash = match kind {
+        ApiKeyKind::Publishable => &credentials.publishable_key_hash,
+        ApiKeyKind::Secret => &credentials.secret_key_hash,
+    };
+    if credentials.mode != mode || hash_api_key(key) != *expected_hash {
+        return Err(invalid());
+    }
+
+    let allowed = (kind == ApiKeyKind::Secret || scope == PAYMENTS_CREATE_SCOPE)
+        && credentials.scopes.iter().any(|granted| granted == scope);
+    if !allowed {
+        return Err(StabuseError::Forbidden(format!(
+            "API key is not allowed the {} scope",
+            scope
+        )));
+    }
+
+    let touch = match kind {
+        ApiKeyKind::Publishable => TOUCH_PUBLISHABLE_KEY,
+        ApiKeyKind::Secret => TOUCH_SECRET_KEY,
+    };
+    if let Err(e) = sqlx::query(touch).bind(credentials.id).execute(pool).await {
+        TracingError!(error = ?e, "Failed to record API key usage");
+    }
+
+    Ok(ApiKeyIdentity {
+        key_id: credentials.id,
+        merchant_id: credentials.merchant_id,
+        kind,
+        mode,
+    })
+}
+
+async fn authenticate_request(
+    req: &ServiceRequest,
+    key: &str,
+    scope: &str,
+) -> Result<ApiKeyIdentity, StabuseError> {
+    let pool = req
+        .app_data::<web::Data<PgPool>>()
+        .ok_or_else(|| StabuseError::Internal("Database pool not configured".to_string()))?;
+
+    authenticate_api_key(pool, key, scope).await
+}
+
+/// Accepts a publishable or secret key allowed to create payments.
+pub async fn payment_api_key_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    match authenticate_request(&req, credentials.token(), PAYMENTS_CREATE_SCOPE).await {
+        Ok(identity) => {
+            req.extensions_mut().insert(identity);
+            Ok(req)
+        }
+        Err(e) => {
+            TracingError!(error = ?e, "API key validation failed");
+            Err((e.into(), req))
+        }
+    }
+}
+
+/// Accepts a merchant JWT, or a secret key allowed to read payments. Key
+/// requests get merchant `Claims` with an empty username so the merchant
+/// handlers serve both alike, plus the key's identity to scope them to its
+/// mode.
+pub async fn merchant_jwt_or_api_key_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    merchant_jwt_or_secret_key(req, credentials, PAYMENTS_READ_SCOPE).await
+}
+
+/// Like [`merchant_jwt_or_api_key_validator`], for creating what fixes the
+/// amount a payer is charged: the key must be a secret key allowed to
+/// create payments.
+pub async fn merchant_jwt_or_api_key_create_validator(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    merchant_jwt_or_secret_key(req, credentials, PAYMENTS_CREATE_SCOPE).await
+}
+
+async fn merchant_jwt_or_secret_key(
+    req: ServiceRequest,
+    credentials: BearerAuth,
+    scope: &str,
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    if !credentials.token().starts_with(SECRET_KEY_PREFIX) {
+        return merchant_jwt_validator(req, credentials).await;
+    }
+
+    match authenticate_request(&req, credentials.token(), scope).await {
+        Ok(identity) => {
+            req.extensions_mut().insert(Claims {
+                sub: identity.merchant_id,
+                username: String::new(),
+                exp: 0,
+                iat: 0,
+            });
+            req.extensions_mut().insert(identity);
+            Ok(req)
+        }
+        Err(e) => {
+            TracingError!(error = ?e, "API key validation failed");
+            Err((e.into(), req))
+        }
+    }
+}
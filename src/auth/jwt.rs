@@ -1,15 +1,15 @@
-use actix_web::{dev::ServiceRequest, HttpMessage};
+use actix_web::{dev::ServiceRequest, http::header::AUTHORIZATION, web, HttpMessage, HttpRequest};
 use actix_web_httpauth::extractors::{
     bearer::{BearerAuth, Config},
     AuthenticationError,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use tracing::error as TracingError;
 
 use crate::{
     error::StabuseError,
-    types::types::{AdminClaims, Claims, PaymentClaims},
+    types::types::{AdminClaims, Claims, PaymentClaims, PaymentTokenQuery},
 };
 
 pub fn generate_merchant_jwt(
@@ -147,20 +147,25 @@ pub async fn admin_jwt_validator(
     }
 }
 
+/// How long a payment stays open when the merchant has not configured an expiry.
+pub const DEFAULT_PAYMENT_EXPIRY_MINUTES: i64 = 30;
+/// How long a payment token outlives the payment's `expires_at`, so a
+/// checkout page can follow a payment sent near the end of its window
+/// through to confirmation.
+const PAYMENT_TOKEN_GRACE_HOURS: i64 = 24;
+
 pub fn generate_payment_jwt(
     pending_payment_id: i32,
     secret: &str,
-    rpc: String,
+    chain_id: i64,
     network: String,
+    expires_at: DateTime<Utc>,
 ) -> Result<String, StabuseError> {
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::minutes(30))
-        .expect("valid timestamp")
-        .timestamp();
+    let expiration = (expires_at + Duration::hours(PAYMENT_TOKEN_GRACE_HOURS)).timestamp();
     let claims = PaymentClaims {
         pending_payment_id: pending_payment_id,
-        rpc: rpc,
         network: network,
+        chain_id,
         exp: expiration,
         iat: Utc::now().timestamp(),
     };
@@ -191,6 +196,29 @@ pub async fn verify_pending_payment_jwt(
     })
 }
 
+/// Verifies the pending payment token of a request that cannot go through
+/// [`pending_payment_jwt_validator`]: the `token` query parameter is used
+/// when present, the bearer token otherwise.
+pub async fn pending_payment_claims(req: &HttpRequest) -> Result<PaymentClaims, StabuseError> {
+    let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+
+    let token = web::Query::<PaymentTokenQuery>::from_query(req.query_string())
+        .ok()
+        .and_then(|query| query.into_inner().token)
+        .or_else(|| {
+            req.headers()
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::to_string)
+        })
+        .ok_or_else(|| StabuseError::Unauthorized("Missing payment token".to_string()))?;
+
+    verify_pending_payment_jwt(&token, jwt_secret)
+        .await
+        .map_err(|e| StabuseError::Unauthorized(e.to_string()))
+}
+
 pub async fn pending_payment_jwt_validator(
     req: ServiceRequest,
     credentials: BearerAuth,
@@ -1,2 +1,3 @@
+pub mod api_key;
 pub mod jwt;
 pub mod otp;
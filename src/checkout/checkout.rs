@@ -0,0 +1,207 @@
+use bigdecimal::{BigDecimal, Zero};
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    core::{adapter::adapter::chain_adapter, rpc::rpc::with_failover},
+    db::migrations::checkout::{
+        inserts_and_updates::{ADD_CHECKOUT_SESSION, SET_CHECKOUT_SESSION_PAYMENT},
+        select_queries::{
+            GET_CHECKOUT_SESSION, GET_MERCHANT_CHECKOUT_OPTIONS, LOCK_OPEN_CHECKOUT_SESSION,
+        },
+    },
+    error::StabuseError,
+    invoice::invoice::check_invoice_payable,
+    network::network::get_network,
+    payment::payment::supersede_pending_payment,
+    types::types::{
+        AccountMode, CheckoutOption, CheckoutSelectionRequest, CheckoutSession,
+        CheckoutSessionStatus, CreateCheckoutSessionRequest, CreatePaymentRequest,
+        PaymentAuthDetails,
+    },
+    utils::secret::generate_secret,
+};
+
+const CHECKOUT_SESSION_PREFIX: &str = "cs_";
+const DEFAULT_CHECKOUT_EXPIRY_MINUTES: i32 = 60;
+const MAX_CHECKOUT_EXPIRY_MINUTES: i32 = 7 * 24 * 60;
+const MAX_ORDER_REFERENCE_LENGTH: usize = 255;
+
+pub async fn create_checkout_session(
+    pool: &PgPool,
+    merchant_id: i32,
+    mode: AccountMode,
+    request: &CreateCheckoutSessionRequest,
+) -> Result<CheckoutSession, StabuseError> {
+    if request.amount <= BigDecimal::zero() {
+        return Err(StabuseError::InvalidData(
+            "Checkout amount must be greater than zero".to_string(),
+        ));
+    }
+
+    let order_reference = request.order_reference.trim();
+    if order_reference.is_empty() || order_reference.len() > MAX_ORDER_REFERENCE_LENGTH {
+        return Err(StabuseError::InvalidData(format!(
+            "Order reference must be between 1 and {} characters",
+            MAX_ORDER_REFERENCE_LENGTH
+        )));
+    }
+
+    let expires_in_minutes = request
+        .expires_in_minutes
+        .unwrap_or(DEFAULT_CHECKOUT_EXPIRY_MINUTES);
+    if !(1..=MAX_CHECKOUT_EXPIRY_MINUTES).contains(&expires_in_minutes) {
+        return Err(StabuseError::InvalidData(format!(
+            "Checkout expiry must be between 1 and {} minutes",
+            MAX_CHECKOUT_EXPIRY_MINUTES
+        )));
+    }
+
+    if let Some(invoice_id) = &request.invoice_id {
+        check_invoice_payable(pool, merchant_id, mode, invoice_id, None, &request.amount).await?;
+    }
+
+    // A merchant with nothing to offer in this mode would hand out a
+    // session nobody can pay.
+    if get_checkout_options(pool, merchant_id, mode)
+        .await?
+        .is_empty()
+    {
+        return Err(StabuseError::InvalidData(format!(
+            "No {} mode network accepts payments for this merchant",
+            mode.as_str()
+        )));
+    }
+
+    let session = sqlx::query_as::<_, CheckoutSession>(ADD_CHECKOUT_SESSION)
+        .bind(format!("{}{}", CHECKOUT_SESSION_PREFIX, generate_secret()))
+        .bind(merchant_id)
+        .bind(mode)
+        .bind(&request.amount)
+        .bind(order_reference)
+        .bind(expires_in_minutes)
+        .bind(&request.invoice_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(session)
+}
+
+pub async fn get_checkout_session(
+    pool: &PgPool,
+    session_id: &str,
+) -> Result<CheckoutSession, StabuseError> {
+    sqlx::query_as::<_, CheckoutSession>(GET_CHECKOUT_SESSION)
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StabuseError::InvalidData("Checkout session not found".to_string()))
+}
+
+/// Every network and asset the merchant accepts in `mode`: networks with a
+/// payout address in `supported_networks`, on testnets for test mode and
+/// mainnets for live mode, and assets enabled on the network.
+pub async fn get_checkout_options(
+    pool: &PgPool,
+    merchant_id: i32,
+    mode: AccountMode,
+) -> Result<Vec<CheckoutOption>, StabuseError> {
+    let options = sqlx::query_as::<_, CheckoutOption>(GET_MERCHANT_CHECKOUT_OPTIONS)
+        .bind(merchant_id)
+        .bind(mode)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(options)
+}
+
+/// Creates the payment for the network and asset the payer picked and
+/// returns the chain-specific transaction to sign. Picking again while the
+/// session is open replaces the earlier choice.
+pub async fn select_checkout_option(
+    pool: &PgPool,
+    session_id: &str,
+    selection: &CheckoutSelectionRequest,
+) -> Result<(Value, PaymentAuthDetails), StabuseError> {
+    let session = get_checkout_session(pool, session_id).await?;
+    if session.status != CheckoutSessionStatus::Open {
+        return Err(StabuseError::InvalidData(
+            "Checkout session is no longer open".to_string(),
+        ));
+    }
+
+    let asset = selection.asset.to_uppercase();
+    let accepted = get_checkout_options(pool, session.merchant_id, session.mode)
+        .await?
+        .iter()
+        .any(|option| option.chain_id == selection.chain_id && option.asset == asset);
+    if !accepted {
+        return Err(StabuseError::InvalidData(format!(
+            "{} on chain {} is not accepted for this checkout",
+            asset, selection.chain_id
+        )));
+    }
+
+    if let Some(invoice_id) = &session.invoice_id {
+        check_invoice_payable(
+            pool,
+            session.merchant_id,
+            session.mode,
+            invoice_id,
+            Some(&asset),
+            &session.amount,
+        )
+        .await?;
+    }
+
+    let network = get_network(pool, selection.chain_id).await?;
+    let adapter = chain_adapter(network.chain_family);
+    let request = CreatePaymentRequest {
+        payment_amount: session.amount.clone(),
+        user_address: selection.payer_address.clone(),
+        asset,
+        chain_id: selection.chain_id,
+        invoice_id: session.invoice_id.clone(),
+    };
+    let (network_ref, request_ref, merchant_id) = (&network, &request, session.merchant_id);
+
+    let (transaction, auth_details) = with_failover(&network, |rpc_url| async move {
+        adapter
+            .create_payment(pool, network_ref, &rpc_url, merchant_id, request_ref)
+            .await
+    })
+    .await?;
+
+    // The payment the session pointed at before is expired in the same
+    // transaction that re-links the session, so only the newest one can be
+    // paid. When the session cannot be re-linked, the new payment is the one
+    // expired instead.
+    let mut tx = pool.begin().await?;
+    let previous: Option<Option<i32>> = sqlx::query_scalar(LOCK_OPEN_CHECKOUT_SESSION)
+        .bind(session_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let rejection = match previous {
+        None => Some("Checkout session closed while the payment was being created"),
+        Some(Some(previous)) if !supersede_pending_payment(&mut tx, previous).await? => {
+            Some("A payment for this checkout session is already under way")
+        }
+        Some(_) => None,
+    };
+
+    if let Some(reason) = rejection {
+        supersede_pending_payment(&mut tx, auth_details.pending_payment_id).await?;
+        tx.commit().await?;
+        return Err(StabuseError::InvalidData(reason.to_string()));
+    }
+
+    sqlx::query(SET_CHECKOUT_SESSION_PAYMENT)
+        .bind(session_id)
+        .bind(auth_details.pending_payment_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((transaction, auth_details))
+}
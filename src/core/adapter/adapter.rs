@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::{
+    core::{evm::evm::EvmAdapter, sol::sol::SolanaAdapter},
+    error::StabuseError,
+    types::types::{
+        ChainFamily, ConfirmationCheck, ConfirmationPolicy, CreatePaymentRequest, NetworkDB,
+        PaymentAuthDetails,
+    },
+};
+
+/// Everything the server needs from a chain. Handlers, the verification
+/// consumer and the confirmation tracker pick an adapter from the network's
+/// `chain_family` and call through it with one RPC endpoint at a time.
+#[async_trait]
+pub trait ChainAdapter: Send + Sync {
+    /// Reads the identifier the chain reports about itself: the EIP-155 chain
+    /// ID on EVM networks, the genesis hash on Solana.
+    async fn chain_identifier(&self, rpc_url: &str) -> Result<String, StabuseError>;
+
+    /// Reads decimals and symbol for a token contract or mint.
+    async fn fetch_asset_metadata(
+        &self,
+        rpc_url: &str,
+        ticker: &str,
+        address: &str,
+    ) -> Result<(u8, String), StabuseError>;
+
+    /// Records a pending payment for the merchant and returns the unsigned
+    /// transfer the payer has to sign.
+    async fn create_payment(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        merchant_id: i32,
+        request: &CreatePaymentRequest,
+    ) -> Result<(Value, PaymentAuthDetails), StabuseError>;
+
+    /// Checks a submitted transaction against its pending payment and marks
+    /// the payment as seen.
+    async fn verify_transaction(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        pending_payment_id: i32,
+        tx_hash: &str,
+    ) -> Result<(), StabuseError>;
+
+    async fn check_confirmations(
+        &self,
+        rpc_url: &str,
+        tx_hash: &str,
+        block_number: u64,
+        block_hash: &str,
+        policy: &ConfirmationPolicy,
+    ) -> Result<ConfirmationCheck, StabuseError>;
+}
+
+pub fn chain_adapter(chain_family: ChainFamily) -> &'static dyn ChainAdapter {
+    match chain_family {
+        ChainFamily::Evm => &EvmAdapter,
+        ChainFamily::Solana => &SolanaAdapter,
+    }
+}
@@ -1,85 +1,153 @@
 use alloy::{
-    consensus::Transaction,
     eips::BlockNumberOrTag,
     hex,
-    primitives::{keccak256, Address, FixedBytes, TxKind, U256},
+    primitives::{Address, FixedBytes, TxKind, U256},
     providers::{Provider, ProviderBuilder},
-    rpc::types::{TransactionInput, TransactionReceipt, TransactionRequest},
+    rpc::types::{Log, TransactionInput, TransactionReceipt, TransactionRequest},
 };
-use alloy_sol_types::{sol, SolCall};
-use bigdecimal::ToPrimitive;
+use alloy_sol_types::{sol, SolCall, SolEvent};
+use async_trait::async_trait;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::{PgPool, Row};
 use std::{env, str::FromStr};
 
 use crate::{
     auth::jwt::generate_payment_jwt,
+    core::adapter::adapter::ChainAdapter,
     db::migrations::payments::{
-        inserts_and_updates::{ADD_PAYMENT, ADD_PENDING_PAYMENT, DELETE_PENDING_PAYMENT},
-        select_queries::GET_PENDING_PAYMENT,
+        inserts_and_updates::ADD_PENDING_PAYMENT, select_queries::GET_PENDING_PAYMENT,
     },
     error::StabuseError,
-    merchant::merchant::get_merchant_network_address,
-    network::network::get_network_and_asset_address_with_chain_id,
+    merchant::merchant::{get_merchant_network_address, get_merchant_payment_expiry},
+    network::network::get_network_asset,
+    payment::payment::{mark_payment_failed, record_payment_transfer},
     types::types::{
-        CreatePaymentTransaction, PaymentAuthDetails, PendingPayment, TransactionValidationParams,
+        AccountMode, ConfirmationCheck, ConfirmationPolicy, CreatePaymentRequest,
+        CreatePaymentTransaction, NetworkDB, PaymentAuthDetails, PendingPayment,
+        TransactionInclusion, TransactionValidationParams,
     },
     utils::{
-        utils::{generate_webhook_url, get_token_decimals},
+        utils::{from_base_units, to_base_units},
         validation::address_validation::validate_address,
     },
 };
 
-const REQUIRED_CONFIRMATIONS: u64 = 12;
-
 sol! {
     #[derive(Debug, Serialize, Deserialize)]
     interface IERC20 {
         function transfer(address to, uint256 value) external returns (bool);
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string);
         event Transfer(address indexed from, address indexed to, uint256 value);
     }
 }
 
+pub struct EvmAdapter;
+
+#[async_trait]
+impl ChainAdapter for EvmAdapter {
+    async fn chain_identifier(&self, rpc_url: &str) -> Result<String, StabuseError> {
+        let rpc = rpc_url
+            .parse()
+            .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
+        let provider = ProviderBuilder::new().on_http(rpc);
+
+        Ok(provider.get_chain_id().await?.to_string())
+    }
+
+    async fn fetch_asset_metadata(
+        &self,
+        rpc_url: &str,
+        _ticker: &str,
+        address: &str,
+    ) -> Result<(u8, String), StabuseError> {
+        fetch_erc20_metadata(rpc_url, address).await
+    }
+
+    async fn create_payment(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        merchant_id: i32,
+        request: &CreatePaymentRequest,
+    ) -> Result<(Value, PaymentAuthDetails), StabuseError> {
+        let (transaction, auth_details) =
+            create_payment_request(pool, merchant_id, request, network, rpc_url).await?;
+
+        Ok((serde_json::to_value(transaction)?, auth_details))
+    }
+
+    async fn verify_transaction(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        pending_payment_id: i32,
+        tx_hash: &str,
+    ) -> Result<(), StabuseError> {
+        verify_signed_transaction(pool, pending_payment_id, network, rpc_url, tx_hash).await
+    }
+
+    async fn check_confirmations(
+        &self,
+        rpc_url: &str,
+        tx_hash: &str,
+        block_number: u64,
+        block_hash: &str,
+        policy: &ConfirmationPolicy,
+    ) -> Result<ConfirmationCheck, StabuseError> {
+        check_transaction_confirmations(rpc_url, tx_hash, block_number, block_hash, policy).await
+    }
+}
+
 pub async fn create_payment_request(
     pool: &PgPool,
     merchant_id: i32,
-    amount: u64,
-    user_address: &str,
+    request: &CreatePaymentRequest,
+    network_config: &NetworkDB,
     rpc_url: &str,
-    asset: &str,
 ) -> Result<(CreatePaymentTransaction, PaymentAuthDetails), StabuseError> {
-    validate_address(user_address)?;
+    let amount = &request.payment_amount;
+    let user_address = request.user_address.as_str();
+    let asset = request.asset.as_str();
+    validate_address(user_address, network_config.chain_family)?;
 
     let rpc = rpc_url
         .parse()
-        .map_err(|e| StabuseError::Internal(format!("Invalid RPC URL: {}", e)))?;
+        .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
     let provider = ProviderBuilder::new().on_http(rpc);
     let chain_id = provider.get_chain_id().await?;
+    ensure_chain_id(chain_id, network_config)?;
 
     let merchant_address =
         get_merchant_network_address(pool, merchant_id, chain_id.try_into().unwrap()).await?;
-    let (network, token_address) =
-        get_network_and_asset_address_with_chain_id(pool, asset, chain_id).await?;
+    let network_asset = get_network_asset(pool, network_config.chain_id, asset).await?;
+    let base_units = to_base_units(amount, network_asset.decimals)?;
+    let network = network_config.name.clone();
     let from_address = Address::from_str(user_address)
-        .map_err(|e| StabuseError::Internal(format!("Invalid user address: {}", e)))?;
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid user address: {}", e)))?;
     let to_address = Address::from_str(&merchant_address)
-        .map_err(|e| StabuseError::Internal(format!("Invalid merchant address: {}", e)))?;
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid merchant address: {}", e)))?;
 
     let transfer_call = IERC20::transferCall {
         to: to_address,
-        value: U256::from(amount),
+        value: base_units,
     };
     let call_data = transfer_call.abi_encode();
-    tracing::info!("Token Address: {}", token_address);
+    tracing::info!("Token Address: {}", network_asset.address);
 
     let nonce = provider.get_transaction_count(from_address).await?;
 
     let gas_estimate = {
         let tx = TransactionRequest {
             from: Some(from_address),
-            to: Some(TxKind::from(Address::from_str(&token_address).map_err(
-                |e| StabuseError::Internal(format!("Invalid token address: {}", e)),
-            )?)),
+            to: Some(TxKind::from(
+                Address::from_str(&network_asset.address)
+                    .map_err(|e| StabuseError::Internal(format!("Invalid token address: {}", e)))?,
+            )),
             input: Some(TransactionInput {
                 input: None,
                 data: Some(call_data.clone().into()),
@@ -103,17 +171,16 @@ pub async fn create_payment_request(
             (None, None)
         };
 
-    let (webhook_url, timestamp) = generate_webhook_url(merchant_id, user_address, amount);
-    tracing::info!("Generated Webhook URL: {}", webhook_url);
-    tracing::info!("Generated Timestamp: {}", timestamp);
-
+    let expires_at = Utc::now() + get_merchant_payment_expiry(pool, merchant_id).await?;
     let pending_payment_id: i32 = sqlx::query(ADD_PENDING_PAYMENT)
         .bind(merchant_id)
         .bind(user_address)
-        .bind(amount.to_string())
+        .bind(amount)
         .bind(asset)
         .bind(network.clone())
-        .bind(webhook_url.clone())
+        .bind(expires_at)
+        .bind(AccountMode::for_network(network_config))
+        .bind(&request.invoice_id)
         .fetch_one(pool)
         .await
         .map_err(|e| StabuseError::DatabaseError(e))?
@@ -124,18 +191,19 @@ pub async fn create_payment_request(
     let token = generate_payment_jwt(
         pending_payment_id,
         &jwt_secret,
-        rpc_url.to_string(),
+        network_config.chain_id,
         network,
+        expires_at,
     )?;
 
     let auth_details = PaymentAuthDetails {
+        pending_payment_id,
         jwt_token: token,
-        webhook_url: webhook_url,
     };
 
     Ok((
         CreatePaymentTransaction {
-            to: token_address,
+            to: network_asset.address,
             from: user_address.to_string(),
             data: hex::encode(call_data),
             value: "0x0".to_string(),
@@ -152,118 +220,53 @@ pub async fn create_payment_request(
 pub async fn verify_signed_transaction(
     pool: &PgPool,
     pending_payment_id: i32,
+    network_config: &NetworkDB,
     rpc_url: &str,
     tx_hash: &str,
-) -> Result<(i32, String), StabuseError> {
+) -> Result<(), StabuseError> {
     let rpc = rpc_url
         .parse()
-        .map_err(|e| StabuseError::Internal(format!("Invalid RPC URL: {}", e)))?;
+        .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
     let provider = ProviderBuilder::new().on_http(rpc);
     let chain_id = provider.get_chain_id().await?;
+    ensure_chain_id(chain_id, network_config)?;
     tracing::info!("Pending payment id: {}", pending_payment_id);
     let pending_payment = sqlx::query_as::<_, PendingPayment>(GET_PENDING_PAYMENT)
         .bind(pending_payment_id)
         .fetch_one(pool)
         .await
         .map_err(|e| StabuseError::DatabaseError(e))?;
-    let (network, token_address) =
-        get_network_and_asset_address_with_chain_id(pool, &pending_payment.asset, chain_id).await?;
+    let network_asset =
+        get_network_asset(pool, network_config.chain_id, &pending_payment.asset).await?;
 
-    let tx_hash_bytes = hex::decode(tx_hash.trim_start_matches("0x"))
-        .map_err(|_| StabuseError::InvalidData("Invalid transaction hash".to_string()))?;
-
-    let tx_hash_array: [u8; 32] = tx_hash_bytes
-        .try_into()
-        .map_err(|_| StabuseError::InvalidData("Invalid transaction hash length".to_string()))?;
-
-    let tx_hash_fixed: FixedBytes<32> = FixedBytes::from(tx_hash_array);
+    let tx_hash_fixed = parse_tx_hash(tx_hash)?;
 
     let receipt = provider
         .get_transaction_receipt(tx_hash_fixed)
         .await
-        .map_err(|e| {
-            StabuseError::Internal(format!("Failed to fetch transaction receipt: {}", e))
-        })?;
+        .map_err(|e| StabuseError::Rpc(format!("Failed to fetch transaction receipt: {}", e)))?;
 
     let receipt = match receipt {
         Some(receipt) => receipt,
         None => {
-            return Err(StabuseError::Internal(format!(
-                "Failed to fetch transaction receipt"
+            return Err(StabuseError::InvalidData(format!(
+                "Transaction {} not found",
+                tx_hash
             )))
         }
     };
 
-    while let Some(tx_block_number) = receipt.block_number {
-        let current_block = provider.get_block_number().await?;
-        let confirmations = current_block.saturating_sub(tx_block_number);
-
-        if confirmations >= REQUIRED_CONFIRMATIONS {
-            break;
-        }
-
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-    }
-
-    let decimal = get_token_decimals(&pending_payment.asset)?;
-    let amount = pending_payment
-        .amount
-        .to_f64()
-        .ok_or_else(|| StabuseError::Internal("Invalid amount".to_string()))?;
-    let converted_amount = (amount * 10f64.powi(decimal as i32)) as u128;
-
-    let tx = provider
-        .get_transaction_by_hash(tx_hash_fixed)
-        .await?
-        .ok_or_else(|| StabuseError::Internal("Transaction not found".to_string()))?;
-
-    let merchant_address = get_merchant_network_address(
-        pool,
-        pending_payment.merchant_id,
-        chain_id.try_into().unwrap(),
-    )
-    .await?;
-
-    let expected_to = Address::from_str(&token_address)
-        .map_err(|e| StabuseError::Internal(format!("Invalid token address: {}", e)))?;
-    let expected_data = IERC20::transferCall {
-        to: Address::from_str(&merchant_address)
-            .map_err(|e| StabuseError::Internal(format!("Invalid merchant address: {}", e)))?,
-        value: U256::from(converted_amount),
-    }
-    .abi_encode();
-
-    let tx_inner = tx.inner;
-    let tx_kind = tx_inner.kind();
-
-    match tx_kind {
-        TxKind::Call(to) => {
-            if to != expected_to {
-                return Err(StabuseError::InvalidData(
-                    "Transaction recipient (to) address does not match.".to_string(),
-                ));
-            }
-        }
+    let inclusion = match (receipt.block_number, receipt.block_hash) {
+        (Some(block_number), Some(block_hash)) => TransactionInclusion {
+            block_number,
+            block_hash: block_hash.to_string(),
+        },
         _ => {
             return Err(StabuseError::InvalidData(
-                "Unsupported transaction kind.".to_string(),
+                "Transaction is not yet included in a block".to_string(),
             ))
         }
-    }
-
-    let tx_data = tx_inner.input();
-
-    if tx_data.to_vec() != expected_data {
-        return Err(StabuseError::InvalidData(
-            "Transaction data does not match the expected data.".to_string(),
-        ));
-    }
-
-    if !receipt.status() {
-        return Err(StabuseError::InvalidData(
-            "Transaction execution failed.".to_string(),
-        ));
-    }
+    };
 
     let validation_params = TransactionValidationParams {
         merchant_address: get_merchant_network_address(
@@ -273,84 +276,197 @@ pub async fn verify_signed_transaction(
         )
         .await?
         .parse()
-        .map_err(|e| StabuseError::Internal(format!("Invalid merchant address: {}", e)))?,
-        token_address: token_address
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid merchant address: {}", e)))?,
+        token_address: network_asset
+            .address
             .parse()
             .map_err(|e| StabuseError::Internal(format!("Invalid token address: {}", e)))?,
         user_address: Address::from_str(&pending_payment.sender)
-            .map_err(|e| StabuseError::Internal(format!("Invalid user address: {}", e)))?,
-        amount: U256::from(converted_amount),
+            .map_err(|e| StabuseError::InvalidData(format!("Invalid user address: {}", e)))?,
     };
 
-    validate_transfer_event(&receipt, &validation_params)?;
+    // A reverted transaction leaves no logs to match, so it only fails the
+    // payment when it was the payer's, or a call to the token; anyone else's
+    // transaction hash is just rejected.
+    if !receipt.status() {
+        let reason = "Transaction execution failed.";
+        let relevant = receipt.from == validation_params.user_address
+            || receipt.to == Some(validation_params.token_address);
+        if relevant {
+            mark_payment_failed(pool, &pending_payment, tx_hash, reason).await?;
+        }
+        return Err(StabuseError::InvalidData(reason.to_string()));
+    }
 
-    sqlx::query(DELETE_PENDING_PAYMENT)
-        .bind(pending_payment_id)
-        .execute(pool)
-        .await
-        .map_err(|e| StabuseError::DatabaseError(e))?;
+    // The outer call is not inspected: whatever contract the transaction went
+    // through, only the token's `Transfer` logs decide what was paid.
+    let transferred = sum_transfer_events(&receipt, &validation_params)?;
+    let amount = from_base_units(transferred, network_asset.decimals);
 
     tracing::info!("Pending payment: {:?}", pending_payment);
-    let id = sqlx::query_scalar(ADD_PAYMENT)
-        .bind(pending_payment.merchant_id)
-        .bind(pending_payment.sender)
-        .bind(pending_payment.amount.to_string())
-        .bind(tx_hash)
-        .bind(pending_payment.asset)
-        .bind(network)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| StabuseError::DatabaseError(e))?;
+    record_payment_transfer(pool, &pending_payment, tx_hash, &inclusion, &amount).await
+}
+
+/// Re-checks the block a transaction was seen in against the canonical chain
+/// and reports how deep it is, or where it moved to after a reorg.
+pub async fn check_transaction_confirmations(
+    rpc_url: &str,
+    tx_hash: &str,
+    block_number: u64,
+    block_hash: &str,
+    policy: &ConfirmationPolicy,
+) -> Result<ConfirmationCheck, StabuseError> {
+    let rpc = rpc_url
+        .parse()
+        .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
+    let provider = ProviderBuilder::new().on_http(rpc);
+
+    let canonical_hash = provider
+        .get_block_by_number(BlockNumberOrTag::Number(block_number), false.into())
+        .await?
+        .map(|block| block.header.hash.to_string());
+
+    if canonical_hash.as_deref() != Some(block_hash) {
+        let receipt = provider
+            .get_transaction_receipt(parse_tx_hash(tx_hash)?)
+            .await?;
+
+        return Ok(
+            match receipt.and_then(|receipt| receipt.block_number.zip(receipt.block_hash)) {
+                Some((block_number, block_hash)) => {
+                    ConfirmationCheck::Moved(TransactionInclusion {
+                        block_number,
+                        block_hash: block_hash.to_string(),
+                    })
+                }
+                None => ConfirmationCheck::Dropped,
+            },
+        );
+    }
+
+    let current_block = provider.get_block_number().await?;
+    let confirmations = current_block.saturating_sub(block_number);
 
-    Ok((id, pending_payment.webhook_url))
+    let satisfied = match policy {
+        ConfirmationPolicy::Depth { blocks } => confirmations >= *blocks,
+        ConfirmationPolicy::Finalized | ConfirmationPolicy::Confirmed => provider
+            .get_block_by_number(BlockNumberOrTag::Finalized, false.into())
+            .await?
+            .is_some_and(|block| block.header.number >= block_number),
+    };
+
+    Ok(ConfirmationCheck::Confirmations {
+        confirmations,
+        satisfied,
+    })
 }
 
-fn validate_transfer_event(
+/// Reads `decimals()` and `symbol()` from an ERC-20 contract so assets are
+/// registered with the values the chain actually uses.
+pub async fn fetch_erc20_metadata(
+    rpc_url: &str,
+    token_address: &str,
+) -> Result<(u8, String), StabuseError> {
+    let rpc = rpc_url
+        .parse()
+        .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
+    let provider = ProviderBuilder::new().on_http(rpc);
+    let token = Address::from_str(token_address)
+        .map_err(|e| StabuseError::InvalidAssetFormat(format!("Invalid token address: {}", e)))?;
+
+    let call = |data: Vec<u8>| TransactionRequest {
+        to: Some(TxKind::Call(token)),
+        input: TransactionInput {
+            input: None,
+            data: Some(data.into()),
+        },
+        ..Default::default()
+    };
+
+    let decimals = provider
+        .call(&call(IERC20::decimalsCall {}.abi_encode()))
+        .await?;
+    let decimals = IERC20::decimalsCall::abi_decode_returns(&decimals, true)
+        .map_err(|e| {
+            StabuseError::InvalidAssetFormat(format!(
+                "{} did not return valid decimals: {}",
+                token_address, e
+            ))
+        })?
+        ._0;
+
+    let symbol = provider
+        .call(&call(IERC20::symbolCall {}.abi_encode()))
+        .await?;
+    let symbol = IERC20::symbolCall::abi_decode_returns(&symbol, true)
+        .map_err(|e| {
+            StabuseError::InvalidAssetFormat(format!(
+                "{} did not return a valid symbol: {}",
+                token_address, e
+            ))
+        })?
+        ._0;
+
+    Ok((decimals, symbol))
+}
+
+/// Guards against a misconfigured RPC endpoint pointing at a different chain
+/// than the one the payment was created for.
+fn ensure_chain_id(reported_chain_id: u64, network_config: &NetworkDB) -> Result<(), StabuseError> {
+    if reported_chain_id != network_config.chain_id as u64 {
+        return Err(StabuseError::Rpc(format!(
+            "RPC endpoint for {} reports chain ID {} instead of {}",
+            network_config.name, reported_chain_id, network_config.chain_id
+        )));
+    }
+
+    Ok(())
+}
+
+fn parse_tx_hash(tx_hash: &str) -> Result<FixedBytes<32>, StabuseError> {
+    let tx_hash_bytes = hex::decode(tx_hash.trim_start_matches("0x"))
+        .map_err(|_| StabuseError::InvalidData("Invalid transaction hash".to_string()))?;
+
+    let tx_hash_array: [u8; 32] = tx_hash_bytes
+        .try_into()
+        .map_err(|_| StabuseError::InvalidData("Invalid transaction hash length".to_string()))?;
+
+    Ok(FixedBytes::from(tx_hash_array))
+}
+
+/// Decodes an ERC-20 `Transfer` log. Receipts carry the logs of internal calls
+/// too, so transfers made by Safe multisigs, ERC-4337 bundles, Permit2 or
+/// router contracts decode the same way as a direct `transfer()` call.
+pub fn decode_transfer_log(log: &Log) -> Option<IERC20::Transfer> {
+    IERC20::Transfer::decode_log_data(&log.inner.data, true).ok()
+}
+
+/// Sums the token `Transfer` logs in a receipt that move funds from the payer
+/// to the merchant, so one transaction may carry several partial transfers.
+fn sum_transfer_events(
     receipt: &TransactionReceipt,
     params: &TransactionValidationParams,
-) -> Result<(), StabuseError> {
-    let transfer_signature = keccak256("Transfer(address,address,uint256)");
-
-    let total_transfer_amount: U256 = receipt
+) -> Result<U256, StabuseError> {
+    let total_transfer_amount = receipt
         .inner
-        .as_receipt()
-        .ok_or(StabuseError::InvalidData("Invalid receipt".to_string()))?
-        .logs
+        .logs()
         .iter()
-        .filter_map(|log| {
-            if log.inner.address != params.token_address {
-                return None;
-            }
-            if log.inner.topics().get(0) != Some(&transfer_signature) {
-                return None;
-            }
-
-            let from = log
-                .inner
-                .topics()
-                .get(1)
-                .and_then(|topic| Some(Address::from_slice(&topic.0[12..])));
-            let to = log
-                .inner
-                .topics()
-                .get(2)
-                .and_then(|topic| Some(Address::from_slice(&topic.0[12..])));
-
-            if from != Some(params.user_address) || to != Some(params.merchant_address) {
-                return None;
-            }
-
-            U256::try_from_be_slice(&log.inner.data.data.0)
+        .filter(|log| log.inner.address == params.token_address)
+        .filter_map(decode_transfer_log)
+        .filter(|transfer| {
+            transfer.from == params.user_address && transfer.to == params.merchant_address
+        })
+        .try_fold(U256::ZERO, |total, transfer| {
+            total.checked_add(transfer.value)
         })
-        .sum();
+        .ok_or_else(|| StabuseError::InvalidData("Transfer amount overflows".to_string()))?;
 
     tracing::info!("total amount: {}", total_transfer_amount);
-    tracing::info!("expected amount: {}", params.amount);
-    if total_transfer_amount != params.amount {
+    if total_transfer_amount.is_zero() {
         return Err(StabuseError::InvalidData(
-            "Total transfer amount does not match expected amount".to_string(),
+            "Transaction contains no matching transfer".to_string(),
         ));
     }
 
-    Ok(())
+    Ok(total_transfer_amount)
 }
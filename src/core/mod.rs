@@ -1,2 +1,4 @@
+pub mod adapter;
 pub mod evm;
+pub mod rpc;
 pub mod sol;
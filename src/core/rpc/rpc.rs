@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::StabuseError,
+    types::types::{NetworkDB, RpcEndpoint},
+};
+
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(30);
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+const HEALTH_DECAY: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy)]
+struct EndpointHealth {
+    score: f64,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl Default for EndpointHealth {
+    fn default() -> Self {
+        EndpointHealth {
+            score: 1.0,
+            consecutive_failures: 0,
+            last_failure: None,
+        }
+    }
+}
+
+impl EndpointHealth {
+    fn is_cooling_down(&self) -> bool {
+        self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES
+            && self
+                .last_failure
+                .is_some_and(|at| at.elapsed() < FAILURE_COOLDOWN)
+    }
+}
+
+fn health_registry() -> &'static Mutex<HashMap<String, EndpointHealth>> {
+    static HEALTH: OnceLock<Mutex<HashMap<String, EndpointHealth>>> = OnceLock::new();
+    HEALTH.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn endpoint_health(url: &str) -> EndpointHealth {
+    health_registry()
+        .lock()
+        .map(|registry| registry.get(url).copied().unwrap_or_default())
+        .unwrap_or_default()
+}
+
+fn record_success(url: &str) {
+    if let Ok(mut registry) = health_registry().lock() {
+        let health = registry.entry(url.to_string()).or_default();
+        health.score = health.score * HEALTH_DECAY + (1.0 - HEALTH_DECAY);
+        health.consecutive_failures = 0;
+    }
+}
+
+fn record_failure(url: &str) {
+    if let Ok(mut registry) = health_registry().lock() {
+        let health = registry.entry(url.to_string()).or_default();
+        health.score *= HEALTH_DECAY;
+        health.consecutive_failures += 1;
+        health.last_failure = Some(Instant::now());
+    }
+}
+
+/// Returns the network's endpoints, best first: endpoints that keep failing
+/// are pushed to the back, the rest are ranked by weight times health score.
+pub fn ordered_endpoints(network: &NetworkDB) -> Vec<String> {
+    let endpoints = if network.rpc_endpoints.is_empty() {
+        vec![RpcEndpoint {
+            url: network.rpc_url.clone(),
+            weight: 1,
+        }]
+    } else {
+        network.rpc_endpoints.clone()
+    };
+
+    let mut ranked: Vec<(bool, f64, String)> = endpoints
+        .into_iter()
+        .map(|endpoint| {
+            let health = endpoint_health(&endpoint.url);
+            (
+                health.is_cooling_down(),
+                endpoint.weight as f64 * health.score,
+                endpoint.url,
+            )
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+    ranked.into_iter().map(|(_, _, url)| url).collect()
+}
+
+/// Runs `op` against the network's endpoints in order until one answers.
+/// RPC failures (`StabuseError::Rpc`) and timeouts move on to the next
+/// endpoint; any other error is an answer and is returned as-is, so `op` is
+/// never re-run because of bad input.
+pub async fn with_failover<T, F, Fut>(network: &NetworkDB, mut op: F) -> Result<T, StabuseError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, StabuseError>>,
+{
+    let mut last_error =
+        StabuseError::Rpc(format!("No RPC endpoints configured for {}", network.name));
+
+    for url in ordered_endpoints(network) {
+        match tokio::time::timeout(ENDPOINT_TIMEOUT, op(url.clone())).await {
+            Ok(Err(StabuseError::Rpc(msg))) => {
+                tracing::warn!("RPC endpoint {} failed for {}: {}", url, network.name, msg);
+                record_failure(&url);
+                last_error = StabuseError::Rpc(msg);
+            }
+            Ok(result) => {
+                record_success(&url);
+                return result;
+            }
+            Err(_) => {
+                tracing::warn!("RPC endpoint {} timed out for {}", url, network.name);
+                record_failure(&url);
+                last_error = StabuseError::Rpc(format!("RPC endpoint {} timed out", url));
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Asks every endpoint of the network and reports whether at least
+/// `network.rpc_quorum` of them agree, as judged by `op`.
+pub async fn quorum_reached<F, Fut>(network: &NetworkDB, mut op: F) -> bool
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<bool, StabuseError>>,
+{
+    let mut agreeing = 0;
+
+    for url in ordered_endpoints(network) {
+        match tokio::time::timeout(ENDPOINT_TIMEOUT, op(url.clone())).await {
+            Ok(Ok(agrees)) => {
+                record_success(&url);
+                if agrees {
+                    agreeing += 1;
+                }
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("Quorum read from {} failed: {}", url, e);
+                record_failure(&url);
+            }
+            Err(_) => record_failure(&url),
+        }
+
+        if agreeing >= network.rpc_quorum {
+            return true;
+        }
+    }
+
+    false
+}
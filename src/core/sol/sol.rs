@@ -1,10 +1,28 @@
-use bigdecimal::ToPrimitive;
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use alloy::primitives::U256;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcBlockConfig, RpcTransactionConfig},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+    },
+    rpc_request::RpcError,
+};
 use solana_sdk::{
-    commitment_config::CommitmentConfig, message::Message, program_pack::Pack, pubkey::Pubkey,
-    signature::Signature, transaction::Transaction,
+    commitment_config::CommitmentConfig,
+    message::{Message, VersionedMessage},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, TransactionDetails, UiTransactionEncoding,
+    UiTransactionStatusMeta,
 };
-use solana_transaction_status::UiTransactionEncoding;
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::{
     instruction::{transfer_checked, TokenInstruction},
@@ -15,38 +33,112 @@ use std::{env, str::FromStr};
 
 use crate::{
     auth::jwt::generate_payment_jwt,
+    core::adapter::adapter::ChainAdapter,
     db::migrations::payments::{
-        inserts_and_updates::{ADD_PAYMENT, ADD_PENDING_PAYMENT, DELETE_PENDING_PAYMENT},
-        select_queries::GET_PENDING_PAYMENT,
+        inserts_and_updates::ADD_PENDING_PAYMENT, select_queries::GET_PENDING_PAYMENT,
     },
     error::StabuseError,
-    merchant::merchant::get_merchant_network_address,
-    network::network::get_network_and_asset_address_with_chain_id,
-    types::types::{PaymentAuthDetails, PendingPayment},
-    utils::utils::{generate_webhook_url, get_solana_network_identifier, get_token_decimals},
+    merchant::merchant::{get_merchant_network_address, get_merchant_payment_expiry},
+    network::network::get_network_asset,
+    payment::payment::{mark_payment_failed, record_payment_transfer},
+    types::types::{
+        AccountMode, ConfirmationCheck, ConfirmationPolicy, CreatePaymentRequest, NetworkAsset,
+        NetworkDB, PaymentAuthDetails, PendingPayment, TransactionInclusion,
+    },
+    utils::{
+        utils::{from_base_units, to_base_units},
+        validation::address_validation::validate_address,
+    },
 };
 
-const REQUIRED_CONFIRMATIONS: u64 = 12;
+pub struct SolanaAdapter;
+
+#[async_trait]
+impl ChainAdapter for SolanaAdapter {
+    async fn chain_identifier(&self, rpc_url: &str) -> Result<String, StabuseError> {
+        let rpc_client = RpcClient::new(rpc_url.to_string());
+        let genesis_hash = rpc_client
+            .get_genesis_hash()
+            .await
+            .map_err(|e| StabuseError::Rpc(format!("Failed to get genesis hash: {}", e)))?;
+
+        Ok(genesis_hash.to_string())
+    }
+
+    async fn fetch_asset_metadata(
+        &self,
+        rpc_url: &str,
+        ticker: &str,
+        address: &str,
+    ) -> Result<(u8, String), StabuseError> {
+        let decimals = fetch_mint_decimals(rpc_url, address).await?;
+        Ok((decimals, ticker.to_string()))
+    }
+
+    async fn create_payment(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        merchant_id: i32,
+        request: &CreatePaymentRequest,
+    ) -> Result<(Value, PaymentAuthDetails), StabuseError> {
+        let (transaction, auth_details) =
+            create_payment_transaction(pool, network, rpc_url, merchant_id, request).await?;
+
+        Ok((serde_json::to_value(transaction)?, auth_details))
+    }
+
+    async fn verify_transaction(
+        &self,
+        pool: &PgPool,
+        network: &NetworkDB,
+        rpc_url: &str,
+        pending_payment_id: i32,
+        tx_hash: &str,
+    ) -> Result<(), StabuseError> {
+        verify_sol_signed_transaction(pool, pending_payment_id, network, rpc_url, tx_hash).await
+    }
+
+    async fn check_confirmations(
+        &self,
+        rpc_url: &str,
+        tx_hash: &str,
+        block_number: u64,
+        block_hash: &str,
+        policy: &ConfirmationPolicy,
+    ) -> Result<ConfirmationCheck, StabuseError> {
+        check_sol_transaction_confirmations(rpc_url, tx_hash, block_number, block_hash, policy)
+            .await
+    }
+}
 
 pub async fn create_payment_transaction(
     pool: &PgPool,
+    network_config: &NetworkDB,
     rpc_url: &str,
-    payer: &str,
     merchant_id: i32,
-    asset: &str,
-    amount: u64,
-) -> Result<(Transaction, PaymentAuthDetails), Box<dyn std::error::Error>> {
+    request: &CreatePaymentRequest,
+) -> Result<(Transaction, PaymentAuthDetails), StabuseError> {
+    let payer = request.user_address.as_str();
+    let asset = request.asset.as_str();
+    let amount = &request.payment_amount;
+    validate_address(payer, network_config.chain_family)?;
     let rpc_client = RpcClient::new(rpc_url.to_string());
-    let chain_id = get_solana_network_identifier(rpc_url)?;
+    ensure_genesis_hash(&rpc_client, network_config).await?;
+    let chain_id = network_config.chain_id;
     let merchant = get_merchant_network_address(pool, merchant_id, chain_id).await?;
-    let (network, token_mint) =
-        get_network_and_asset_address_with_chain_id(pool, asset, chain_id as u64).await?;
-    let payer_pubkey = Pubkey::from_str(payer)?;
-    let merchant_pubkey = Pubkey::from_str(merchant.as_str())?;
-    let token_mint_pubkey = Pubkey::from_str(token_mint.as_str())?;
-    let account_data = rpc_client.get_account_data(&token_mint_pubkey)?;
-    let mint = Mint::unpack(&account_data)?;
-    let decimals = mint.decimals;
+    let network_asset = get_network_asset(pool, chain_id, asset).await?;
+    let network = network_config.name.clone();
+    let payer_pubkey = Pubkey::from_str(payer)
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid payer address: {}", e)))?;
+    let merchant_pubkey = Pubkey::from_str(merchant.as_str())
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid merchant address: {}", e)))?;
+    let token_mint_pubkey = Pubkey::from_str(network_asset.address.as_str())
+        .map_err(|e| StabuseError::InvalidAssetFormat(format!("Invalid mint address: {}", e)))?;
+    let decimals = network_asset.decimals;
+    let base_units = u64::try_from(to_base_units(amount, decimals)?)
+        .map_err(|_| StabuseError::InvalidData(format!("{} is too large for {}", amount, asset)))?;
 
     let user_token_account = get_associated_token_address(&payer_pubkey, &token_mint_pubkey);
     let merchant_token_account = get_associated_token_address(&merchant_pubkey, &token_mint_pubkey);
@@ -58,26 +150,26 @@ pub async fn create_payment_transaction(
         &merchant_token_account,
         &payer_pubkey,
         &[],
-        amount,
+        base_units,
         decimals,
-    )?;
+    )
+    .map_err(|e| StabuseError::Internal(format!("Failed to build transfer: {}", e)))?;
 
     let message = Message::new(&[transfer_instruction], Some(&payer_pubkey));
-    let _recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let _recent_blockhash = rpc_client.get_latest_blockhash().await?;
 
     let transaction = Transaction::new_unsigned(message);
 
-    let (webhook_url, timestamp) = generate_webhook_url(merchant_id, payer, amount);
-    tracing::info!("Generated Webhook URL: {}", webhook_url);
-    tracing::info!("Generated Timestamp: {}", timestamp);
-
+    let expires_at = Utc::now() + get_merchant_payment_expiry(pool, merchant_id).await?;
     let pending_payment_id: i32 = sqlx::query(ADD_PENDING_PAYMENT)
         .bind(merchant_id)
         .bind(payer)
-        .bind(amount.to_string())
+        .bind(amount)
         .bind(asset)
         .bind(network.clone())
-        .bind(webhook_url.clone())
+        .bind(expires_at)
+        .bind(AccountMode::for_network(network_config))
+        .bind(&request.invoice_id)
         .fetch_one(pool)
         .await?
         .get(0);
@@ -87,13 +179,14 @@ pub async fn create_payment_transaction(
     let token = generate_payment_jwt(
         pending_payment_id,
         &jwt_secret,
-        rpc_url.to_string(),
+        chain_id,
         network,
+        expires_at,
     )?;
 
     let auth_details = PaymentAuthDetails {
+        pending_payment_id,
         jwt_token: token,
-        webhook_url: webhook_url.clone(),
     };
 
     Ok((transaction, auth_details))
@@ -102,9 +195,10 @@ pub async fn create_payment_transaction(
 pub async fn verify_sol_signed_transaction(
     pool: &PgPool,
     pending_payment_id: i32,
+    network_config: &NetworkDB,
     rpc_url: &str,
     tx_hash: &str,
-) -> Result<(i32, String), StabuseError> {
+) -> Result<(), StabuseError> {
     let pending_payment = sqlx::query_as::<_, PendingPayment>(GET_PENDING_PAYMENT)
         .bind(pending_payment_id)
         .fetch_one(pool)
@@ -112,15 +206,13 @@ pub async fn verify_sol_signed_transaction(
         .map_err(|e| StabuseError::DatabaseError(e))?;
 
     let rpc_client = RpcClient::new(rpc_url.to_string());
-    let chain_id = get_solana_network_identifier(rpc_url)?;
+    ensure_genesis_hash(&rpc_client, network_config).await?;
+    let chain_id = network_config.chain_id;
 
-    let (network, _token_mint) =
-        get_network_and_asset_address_with_chain_id(pool, &pending_payment.asset, chain_id as u64)
-            .await?;
+    let network_asset = get_network_asset(pool, chain_id, &pending_payment.asset).await?;
 
-    let signature = Signature::from_str(tx_hash).map_err(|e| {
-        StabuseError::Internal(format!("Failed to parse transaction signature: {}", e))
-    })?;
+    let signature = Signature::from_str(tx_hash)
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid transaction signature: {}", e)))?;
 
     let transaction = rpc_client
         .get_transaction_with_config(
@@ -128,78 +220,242 @@ pub async fn verify_sol_signed_transaction(
             RpcTransactionConfig {
                 encoding: Some(UiTransactionEncoding::Base64),
                 commitment: Some(CommitmentConfig::confirmed()),
-                ..Default::default()
+                max_supported_transaction_version: Some(0),
             },
         )
-        .map_err(|e| StabuseError::Internal(format!("Failed to fetch transaction: {}", e)))?;
+        .await
+        .map_err(|e| StabuseError::Rpc(format!("Failed to fetch transaction: {}", e)))?;
 
-    let current_slot = rpc_client
-        .get_slot()
-        .map_err(|e| StabuseError::Internal(format!("Failed to get current slot: {}", e)))?;
     let tx_slot = transaction.slot;
-    let confirmations = current_slot.saturating_sub(tx_slot);
-
-    if confirmations < REQUIRED_CONFIRMATIONS {
-        return Err(StabuseError::InvalidData(
-            "Insufficient transaction confirmations".to_string(),
-        ));
-    }
+    let block_hash = get_slot_blockhash(&rpc_client, tx_slot).await?;
 
     let tx_meta = transaction
         .transaction
         .meta
         .ok_or_else(|| StabuseError::InvalidData("No transaction metadata".to_string()))?;
 
-    if !tx_meta.status.is_ok() {
-        return Err(StabuseError::InvalidData(
-            "Transaction execution failed".to_string(),
-        ));
-    }
-
-    let decoded_transaction = transaction.transaction.transaction.decode().unwrap();
+    let decoded_transaction = transaction
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| StabuseError::InvalidData("Transaction could not be decoded".to_string()))?;
+    let account_keys = transaction_account_keys(&decoded_transaction.message, Some(&tx_meta));
 
-    validate_transfer_instruction(
+    let transferred = sum_transfer_instructions(
         pool,
-        &decoded_transaction.into_legacy_transaction().unwrap(),
+        &decoded_transaction.message,
+        &account_keys,
         &pending_payment,
-        &pending_payment.asset,
+        &network_asset,
         chain_id,
     )
     .await?;
 
-    sqlx::query(DELETE_PENDING_PAYMENT)
-        .bind(pending_payment_id)
-        .execute(pool)
+    if tx_meta.status.is_err() {
+        let reason = "Transaction execution failed";
+        mark_payment_failed(pool, &pending_payment, tx_hash, reason).await?;
+        return Err(StabuseError::InvalidData(reason.to_string()));
+    }
+
+    let inclusion = TransactionInclusion {
+        block_number: tx_slot,
+        block_hash,
+    };
+    let amount = from_base_units(U256::from(transferred), network_asset.decimals);
+
+    record_payment_transfer(pool, &pending_payment, tx_hash, &inclusion, &amount).await
+}
+
+/// Re-checks the slot a transaction was seen in and reports how many slots
+/// have passed since, or where it landed if its block was abandoned. Only a
+/// node that no longer knows the signature makes it `Dropped`; failing to
+/// reach the node is an error.
+pub async fn check_sol_transaction_confirmations(
+    rpc_url: &str,
+    tx_hash: &str,
+    slot: u64,
+    block_hash: &str,
+    policy: &ConfirmationPolicy,
+) -> Result<ConfirmationCheck, StabuseError> {
+    let rpc_client = RpcClient::new(rpc_url.to_string());
+    let signature = Signature::from_str(tx_hash)
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid transaction signature: {}", e)))?;
+
+    let canonical_hash = find_slot_blockhash(&rpc_client, slot).await?;
+
+    if canonical_hash.as_deref() != Some(block_hash) {
+        let status = rpc_client
+            .get_signature_statuses_with_history(&[signature])
+            .await
+            .map_err(|e| StabuseError::Rpc(format!("Failed to get signature status: {}", e)))?
+            .value
+            .into_iter()
+            .next()
+            .flatten();
+
+        return Ok(match status {
+            Some(status) => ConfirmationCheck::Moved(TransactionInclusion {
+                block_number: status.slot,
+                block_hash: get_slot_blockhash(&rpc_client, status.slot).await?,
+            }),
+            None => ConfirmationCheck::Dropped,
+        });
+    }
+
+    let current_slot = rpc_client
+        .get_slot()
         .await
-        .map_err(|e| StabuseError::DatabaseError(e))?;
+        .map_err(|e| StabuseError::Rpc(format!("Failed to get current slot: {}", e)))?;
+
+    let confirmations = current_slot.saturating_sub(slot);
+
+    let satisfied = match policy {
+        ConfirmationPolicy::Depth { blocks } => confirmations >= *blocks,
+        ConfirmationPolicy::Confirmed | ConfirmationPolicy::Finalized => {
+            let commitment = if *policy == ConfirmationPolicy::Finalized {
+                CommitmentConfig::finalized()
+            } else {
+                CommitmentConfig::confirmed()
+            };
+            rpc_client
+                .get_signature_statuses(&[signature])
+                .await
+                .map_err(|e| StabuseError::Rpc(format!("Failed to get signature status: {}", e)))?
+                .value
+                .into_iter()
+                .next()
+                .flatten()
+                .is_some_and(|status| status.satisfies_commitment(commitment))
+        }
+    };
 
-    let id = sqlx::query_scalar(ADD_PAYMENT)
-        .bind(pending_payment.merchant_id)
-        .bind(pending_payment.sender)
-        .bind(pending_payment.amount.to_string())
-        .bind(tx_hash)
-        .bind(pending_payment.asset)
-        .bind(network)
-        .fetch_one(pool)
+    Ok(ConfirmationCheck::Confirmations {
+        confirmations,
+        satisfied,
+    })
+}
+
+/// Reads the decimals from an SPL mint account. Mints carry no symbol of their
+/// own, so callers register the asset under its ticker.
+pub async fn fetch_mint_decimals(rpc_url: &str, mint: &str) -> Result<u8, StabuseError> {
+    let rpc_client = RpcClient::new(rpc_url.to_string());
+    let mint_pubkey = Pubkey::from_str(mint)
+        .map_err(|e| StabuseError::InvalidAssetFormat(format!("Invalid mint address: {}", e)))?;
+
+    let account_data = rpc_client
+        .get_account_data(&mint_pubkey)
         .await
-        .map_err(|e| StabuseError::DatabaseError(e))?;
+        .map_err(|e| StabuseError::Rpc(format!("Failed to fetch mint account: {}", e)))?;
+    let mint = Mint::unpack(&account_data).map_err(|e| {
+        StabuseError::InvalidAssetFormat(format!("{} is not an SPL mint: {}", mint, e))
+    })?;
+
+    Ok(mint.decimals)
+}
+
+/// Guards against a misconfigured RPC endpoint pointing at a different cluster
+/// than the network it is registered under.
+async fn ensure_genesis_hash(
+    rpc_client: &RpcClient,
+    network_config: &NetworkDB,
+) -> Result<(), StabuseError> {
+    let genesis_hash = rpc_client
+        .get_genesis_hash()
+        .await
+        .map_err(|e| StabuseError::Rpc(format!("Failed to get genesis hash: {}", e)))?
+        .to_string();
+
+    if genesis_hash != network_config.chain_identifier {
+        return Err(StabuseError::Rpc(format!(
+            "RPC endpoint for {} reports genesis hash {} instead of {}",
+            network_config.name, genesis_hash, network_config.chain_identifier
+        )));
+    }
+
+    Ok(())
+}
+
+/// Every account a transaction refers to, in the order its instructions
+/// index them: the static keys, then the writable and readonly keys a v0
+/// transaction loads from address lookup tables.
+pub fn transaction_account_keys(
+    message: &VersionedMessage,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Vec<Pubkey> {
+    let mut account_keys = message.static_account_keys().to_vec();
+
+    if let Some(OptionSerializer::Some(loaded)) = meta.map(|meta| &meta.loaded_addresses) {
+        account_keys.extend(
+            loaded
+                .writable
+                .iter()
+                .chain(&loaded.readonly)
+                .filter_map(|key| Pubkey::from_str(key).ok()),
+        );
+    }
+
+    account_keys
+}
 
-    Ok((id, pending_payment.webhook_url))
+/// Whether the node answered that `slot` was skipped, and so has no block,
+/// rather than failing to answer.
+pub fn is_skipped_slot(error: &ClientError) -> bool {
+    matches!(
+        error.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_SLOT_SKIPPED
+                || *code == JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+    )
+}
+
+async fn get_slot_blockhash(rpc_client: &RpcClient, slot: u64) -> Result<String, StabuseError> {
+    find_slot_blockhash(rpc_client, slot)
+        .await?
+        .ok_or_else(|| StabuseError::Rpc(format!("Slot {} was skipped", slot)))
 }
 
-async fn validate_transfer_instruction(
+/// The hash of the block at `slot`, or `None` if the slot was skipped.
+async fn find_slot_blockhash(
+    rpc_client: &RpcClient,
+    slot: u64,
+) -> Result<Option<String>, StabuseError> {
+    let block = rpc_client
+        .get_block_with_config(
+            slot,
+            RpcBlockConfig {
+                encoding: None,
+                transaction_details: Some(TransactionDetails::None),
+                rewards: Some(false),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await;
+
+    match block {
+        Ok(block) => Ok(Some(block.blockhash)),
+        Err(e) if is_skipped_slot(&e) => Ok(None),
+        Err(e) => Err(StabuseError::Rpc(format!("Failed to fetch block: {}", e))),
+    }
+}
+
+/// Sums the `TransferChecked` instructions that move the asset from the
+/// payer's token account to the merchant's, so a payment may be split across
+/// several instructions.
+async fn sum_transfer_instructions(
     pool: &PgPool,
-    transaction: &Transaction,
+    message: &VersionedMessage,
+    account_keys: &[Pubkey],
     pending_payment: &PendingPayment,
-    token_mint: &str,
+    network_asset: &NetworkAsset,
     chain_id: i64,
-) -> Result<(), StabuseError> {
+) -> Result<u64, StabuseError> {
     let merchant_address =
         get_merchant_network_address(pool, pending_payment.merchant_id, chain_id)
             .await
             .map_err(|e| StabuseError::Internal(format!("Failed to get merchant address {}", e)))?;
 
-    let token_mint_pubkey = Pubkey::from_str(token_mint)
+    let token_mint_pubkey = Pubkey::from_str(&network_asset.address)
         .map_err(|e| StabuseError::Internal(format!("Failed to get token mint: {}", e)))?;
     let merchant_pubkey = Pubkey::from_str(&merchant_address)
         .map_err(|e| StabuseError::Internal(format!("Failed to get merchant pubkey: {}", e)))?;
@@ -209,48 +465,44 @@ async fn validate_transfer_instruction(
     let payer_token_account = get_associated_token_address(&payer_pubkey, &token_mint_pubkey);
     let merchant_token_account = get_associated_token_address(&merchant_pubkey, &token_mint_pubkey);
 
-    let matching_instruction = transaction.message.instructions.iter().find(|instruction| {
-        let program_ids: Vec<Pubkey> = transaction
-            .message
-            .program_ids()
-            .into_iter()
-            .cloned()
-            .collect();
-
-        let instruction_program_id = instruction.program_id(&program_ids);
+    let mut total: u64 = 0;
 
-        if *instruction_program_id != spl_token::id() {
-            return false;
+    for instruction in message.instructions() {
+        if account_keys.get(instruction.program_id_index as usize) != Some(&spl_token::id()) {
+            continue;
         }
 
-        if let Ok(token_instruction) = TokenInstruction::unpack(&instruction.data) {
-            match token_instruction {
-                TokenInstruction::TransferChecked { amount, decimals } => {
-                    let token_decimals = get_token_decimals(&pending_payment.asset).unwrap_or(0);
-
-                    // Use direct comparison of Pubkeys instead of strings
-                    instruction.accounts.len() >= 4
-                        && Pubkey::from_str(&instruction.accounts[0].to_string()).unwrap()
-                            == payer_token_account
-                        && Pubkey::from_str(&instruction.accounts[1].to_string()).unwrap()
-                            == token_mint_pubkey
-                        && Pubkey::from_str(&instruction.accounts[2].to_string()).unwrap()
-                            == merchant_token_account
-                        && Pubkey::from_str(&instruction.accounts[3].to_string()).unwrap()
-                            == payer_pubkey
-                        && amount == pending_payment.amount.to_u64().unwrap()
-                        && decimals == token_decimals
-                }
-                _ => false,
-            }
-        } else {
-            false
+        let Ok(TokenInstruction::TransferChecked { amount, decimals }) =
+            TokenInstruction::unpack(&instruction.data)
+        else {
+            continue;
+        };
+
+        // TransferChecked accounts: [source, mint, destination, authority]
+        let accounts: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .filter_map(|index| account_keys.get(*index as usize).cloned())
+            .collect();
+
+        if accounts.len() >= 4
+            && accounts[0] == payer_token_account
+            && accounts[1] == token_mint_pubkey
+            && accounts[2] == merchant_token_account
+            && accounts[3] == payer_pubkey
+            && decimals == network_asset.decimals
+        {
+            total = total.checked_add(amount).ok_or_else(|| {
+                StabuseError::InvalidData("Transfer amount overflows".to_string())
+            })?;
         }
-    });
+    }
+
+    if total == 0 {
+        return Err(StabuseError::InvalidData(
+            "No matching transfer instruction found".to_string(),
+        ));
+    }
 
-    matching_instruction
-        .ok_or_else(|| {
-            StabuseError::InvalidData("No matching transfer instruction found".to_string())
-        })
-        .map(|_| ())
+    Ok(total)
 }
@@ -6,46 +6,131 @@ use super::migrations::{
     admins::create_admins_table::{
         CREATE_ADMINS_TABLE, CREATE_ADMIN_INVITES_TABLE, CREATE_OTP_TABLE,
     },
+    api_keys::create_api_keys_table::{CREATE_API_KEYS_TABLE, CREATE_INDEX_API_KEYS_MERCHANT_ID},
+    checkout::create_checkout_sessions_table::{
+        CREATE_CHECKOUT_SESSIONS_TABLE, CREATE_INDEX_CHECKOUT_SESSIONS_PENDING_PAYMENT_ID,
+    },
+    invoices::create_invoices_table::{
+        CREATE_INDEX_INVOICES_MERCHANT_ID, CREATE_INVOICES_TABLE, CREATE_INVOICE_LINE_ITEMS_TABLE,
+    },
     merchants::{
         create_merchants_table::CREATE_MERCHANT_TABLE, triggers::TRIGGER_FUNCTION_MERCHANTS,
     },
     networks::{
-        create_indexes::{
-            CREATE_INDEX_BUSD, CREATE_INDEX_DAI, CREATE_INDEX_USDC, CREATE_INDEX_USDT,
+        create_indexes::CREATE_INDEX_NETWORK_ASSETS_ENABLED,
+        create_networks_table::{
+            CREATE_NETWORK_ASSETS_TABLE, CREATE_NETWORK_TABLE, CREATE_WATCHER_CHECKPOINTS_TABLE,
         },
-        create_networks_table::CREATE_NETWORK_TABLE,
-        triggers_and_functions::{TRIGGER, TRIGGER_FUNCTION},
+        triggers_and_functions::{TRIGGER, TRIGGER_FUNCTION, TRIGGER_FUNCTION_NETWORK_ASSETS},
     },
     payments::{
-        create_indexes::{CREATE_INDEX_MERCHANT_ID, CREATE_INDEX_NETWORK, CREATE_INDEX_TX_HASH},
-        create_payments_table::{CREATE_PAYMENTS_TABLE, CREATE_PENDING_PAYMENTS_TABLE},
+        create_indexes::{
+            CREATE_INDEX_INVOICE_ID, CREATE_INDEX_MERCHANT_ID, CREATE_INDEX_NETWORK,
+            CREATE_INDEX_PAYMENT_TRANSFERS_PENDING_ID, CREATE_INDEX_PENDING_EXPIRES_AT,
+            CREATE_INDEX_PENDING_STATUS, CREATE_INDEX_TX_HASH,
+        },
+        create_payments_table::{
+            CREATE_PAYMENTS_TABLE, CREATE_PAYMENT_TRANSFERS_TABLE, CREATE_PENDING_PAYMENTS_TABLE,
+        },
+        triggers::TRIGGER_FUNCTION_PENDING_PAYMENTS,
+    },
+    verification_jobs::create_verification_jobs_table::{
+        CREATE_INDEX_VERIFICATION_JOBS_RUN_AT, CREATE_VERIFICATION_JOBS_TABLE,
+    },
+    webhooks::create_webhooks_table::{
+        CREATE_INDEX_WEBHOOK_DELIVERIES_DUE, CREATE_INDEX_WEBHOOK_DELIVERY_ATTEMPTS_DELIVERY_ID,
+        CREATE_WEBHOOK_DELIVERIES_TABLE, CREATE_WEBHOOK_DELIVERY_ATTEMPTS_TABLE,
+        CREATE_WEBHOOK_ENDPOINTS_TABLE, CREATE_WEBHOOK_EVENTS_TABLE,
     },
 };
 
 pub async fn init_db(pool: &PgPool) -> Result<(), StabuseError> {
     sqlx::query(CREATE_NETWORK_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_NETWORK_ASSETS_TABLE).execute(pool).await?;
     sqlx::query(CREATE_MERCHANT_TABLE).execute(pool).await?;
-    sqlx::query(CREATE_PAYMENTS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_API_KEYS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_INDEX_API_KEYS_MERCHANT_ID)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INVOICES_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_INVOICE_LINE_ITEMS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_INVOICES_MERCHANT_ID)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_WEBHOOK_ENDPOINTS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_WEBHOOK_EVENTS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_WEBHOOK_DELIVERIES_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_WEBHOOK_DELIVERY_ATTEMPTS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_WEBHOOK_DELIVERIES_DUE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_WEBHOOK_DELIVERY_ATTEMPTS_DELIVERY_ID)
+        .execute(pool)
+        .await?;
     sqlx::query(CREATE_PENDING_PAYMENTS_TABLE)
         .execute(pool)
         .await?;
+    sqlx::query(CREATE_PAYMENTS_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_PAYMENT_TRANSFERS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_CHECKOUT_SESSIONS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_CHECKOUT_SESSIONS_PENDING_PAYMENT_ID)
+        .execute(pool)
+        .await?;
     sqlx::query(TRIGGER).execute(pool).await?;
     sqlx::query(TRIGGER_FUNCTION).execute(pool).await?;
     sqlx::query(TRIGGER_FUNCTION_MERCHANTS)
         .execute(pool)
         .await?;
-    sqlx::query(CREATE_INDEX_USDT).execute(pool).await?;
-    sqlx::query(CREATE_INDEX_DAI).execute(pool).await?;
-    sqlx::query(CREATE_INDEX_USDC).execute(pool).await?;
-    sqlx::query(CREATE_INDEX_BUSD).execute(pool).await?;
+    sqlx::query(TRIGGER_FUNCTION_NETWORK_ASSETS)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_NETWORK_ASSETS_ENABLED)
+        .execute(pool)
+        .await?;
     sqlx::query(CREATE_INDEX_MERCHANT_ID).execute(pool).await?;
     sqlx::query(CREATE_INDEX_NETWORK).execute(pool).await?;
     sqlx::query(CREATE_INDEX_TX_HASH).execute(pool).await?;
+    sqlx::query(CREATE_INDEX_INVOICE_ID).execute(pool).await?;
+    sqlx::query(CREATE_INDEX_PENDING_STATUS)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_PENDING_EXPIRES_AT)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_PAYMENT_TRANSFERS_PENDING_ID)
+        .execute(pool)
+        .await?;
+    sqlx::query(TRIGGER_FUNCTION_PENDING_PAYMENTS)
+        .execute(pool)
+        .await?;
     sqlx::query(CREATE_ADMINS_TABLE).execute(pool).await?;
     sqlx::query(CREATE_ADMIN_INVITES_TABLE)
         .execute(pool)
         .await?;
     sqlx::query(CREATE_OTP_TABLE).execute(pool).await?;
+    sqlx::query(CREATE_WATCHER_CHECKPOINTS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_VERIFICATION_JOBS_TABLE)
+        .execute(pool)
+        .await?;
+    sqlx::query(CREATE_INDEX_VERIFICATION_JOBS_RUN_AT)
+        .execute(pool)
+        .await?;
 
     Ok(())
 }
@@ -0,0 +1,18 @@
+pub const CREATE_API_KEYS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    merchant_id INT NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    lookup_id VARCHAR(16) UNIQUE NOT NULL,
+    publishable_key_hash VARCHAR(64) NOT NULL,
+    secret_key_hash VARCHAR(64) NOT NULL,
+    scopes TEXT[] NOT NULL DEFAULT '{}',
+    publishable_last_used_at TIMESTAMPTZ,
+    secret_last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMPTZ
+)"#;
+
+pub const CREATE_INDEX_API_KEYS_MERCHANT_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_api_keys_merchant_id ON api_keys (merchant_id)"#;
@@ -0,0 +1,30 @@
+pub const ADD_API_KEY: &str = r#"
+    INSERT INTO api_keys
+        (merchant_id, name, lookup_id, publishable_key_hash, secret_key_hash, scopes, mode)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING id, name, mode, 'pk_' || mode || '_' || lookup_id AS publishable_prefix,
+        'sk_' || mode || '_' || lookup_id AS secret_prefix, scopes, publishable_last_used_at, secret_last_used_at, created_at, revoked_at
+"#;
+
+pub const REVOKE_API_KEY: &str = r#"
+    UPDATE api_keys
+    SET revoked_at = CURRENT_TIMESTAMP
+    WHERE id = $1 AND merchant_id = $2 AND revoked_at IS NULL
+    RETURNING id
+"#;
+
+pub const TOUCH_PUBLISHABLE_KEY: &str = r#"
+    UPDATE api_keys
+    SET publishable_last_used_at = CURRENT_TIMESTAMP
+    WHERE id = $1
+      AND (publishable_last_used_at IS NULL
+        OR publishable_last_used_at < CURRENT_TIMESTAMP - INTERVAL '1 minute')
+"#;
+
+pub const TOUCH_SECRET_KEY: &str = r#"
+    UPDATE api_keys
+    SET secret_last_used_at = CURRENT_TIMESTAMP
+    WHERE id = $1
+      AND (secret_last_used_at IS NULL
+        OR secret_last_used_at < CURRENT_TIMESTAMP - INTERVAL '1 minute')
+"#;
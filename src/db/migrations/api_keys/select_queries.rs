@@ -0,0 +1,19 @@
+pub const GET_MERCHANT_API_KEYS: &str = r#"
+    SELECT id, name, mode, 'pk_' || mode || '_' || lookup_id AS publishable_prefix,
+        'sk_' || mode || '_' || lookup_id AS secret_prefix, scopes, publishable_last_used_at, secret_last_used_at, created_at, revoked_at
+    FROM api_keys
+    WHERE merchant_id = $1
+    ORDER BY id DESC
+"#;
+
+pub const COUNT_ACTIVE_MERCHANT_API_KEYS: &str = r#"
+    SELECT COUNT(*)
+    FROM api_keys
+    WHERE merchant_id = $1 AND revoked_at IS NULL
+"#;
+
+pub const GET_ACTIVE_API_KEY_BY_LOOKUP_ID: &str = r#"
+    SELECT id, merchant_id, mode, publishable_key_hash, secret_key_hash, scopes
+    FROM api_keys
+    WHERE lookup_id = $1 AND revoked_at IS NULL
+"#;
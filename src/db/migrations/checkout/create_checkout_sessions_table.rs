@@ -0,0 +1,19 @@
+pub const CREATE_CHECKOUT_SESSIONS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS checkout_sessions (
+    id SERIAL PRIMARY KEY,
+    public_id VARCHAR(64) UNIQUE NOT NULL,
+    merchant_id INT NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    amount NUMERIC(38,18) NOT NULL CHECK (amount > 0),
+    order_reference VARCHAR(255) NOT NULL,
+    invoice_id VARCHAR(64) REFERENCES invoices(public_id) ON DELETE SET NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'open',
+    pending_payment_id INT REFERENCES pending_payments(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMPTZ
+)"#;
+
+pub const CREATE_INDEX_CHECKOUT_SESSIONS_PENDING_PAYMENT_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_checkout_sessions_pending_payment_id
+    ON checkout_sessions (pending_payment_id)"#;
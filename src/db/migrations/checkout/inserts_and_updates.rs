@@ -0,0 +1,23 @@
+pub const ADD_CHECKOUT_SESSION: &str = r#"
+    INSERT INTO checkout_sessions
+        (public_id, merchant_id, mode, amount, order_reference, expires_at, invoice_id)
+    VALUES ($1, $2, $3, $4::NUMERIC, $5, CURRENT_TIMESTAMP + make_interval(mins => $6), $7)
+    RETURNING public_id AS session_id, merchant_id, mode, amount, order_reference, invoice_id, status,
+        pending_payment_id, expires_at, created_at, completed_at
+"#;
+
+pub const SET_CHECKOUT_SESSION_PAYMENT: &str = r#"
+    UPDATE checkout_sessions
+    SET pending_payment_id = $2
+    WHERE public_id = $1
+      AND status = 'open'
+      AND expires_at > CURRENT_TIMESTAMP
+    RETURNING id
+"#;
+
+pub const COMPLETE_CHECKOUT_SESSION: &str = r#"
+    UPDATE checkout_sessions
+    SET status = 'completed',
+        completed_at = CURRENT_TIMESTAMP
+    WHERE pending_payment_id = $1 AND status = 'open'
+"#;
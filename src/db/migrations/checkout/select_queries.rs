@@ -0,0 +1,34 @@
+pub const GET_CHECKOUT_SESSION: &str = r#"
+    SELECT public_id AS session_id, merchant_id, mode, amount, order_reference, invoice_id,
+        CASE WHEN status = 'open' AND expires_at <= CURRENT_TIMESTAMP THEN 'expired' ELSE status END
+            AS status,
+        pending_payment_id, expires_at, created_at, completed_at
+    FROM checkout_sessions
+    WHERE public_id = $1
+"#;
+
+pub const LOCK_OPEN_CHECKOUT_SESSION: &str = r#"
+    SELECT pending_payment_id
+    FROM checkout_sessions
+    WHERE public_id = $1
+      AND status = 'open'
+      AND expires_at > CURRENT_TIMESTAMP
+    FOR UPDATE
+"#;
+
+pub const GET_MERCHANT_CHECKOUT_OPTIONS: &str = r#"
+    SELECT n.chain_id, n.name AS network, a.ticker AS asset, a.symbol
+    FROM merchants m
+    CROSS JOIN LATERAL jsonb_each(COALESCE(m.supported_networks, '{}'::jsonb)) AS sn(chain_id, config)
+    JOIN networks n ON n.chain_id::TEXT = sn.chain_id
+    JOIN network_assets a ON a.chain_id = n.chain_id AND a.enabled
+    WHERE m.id = $1
+      AND n.is_testnet = ($2 = 'test')
+      AND jsonb_typeof(sn.config) = 'object'
+      AND sn.config ->> 'address' IS NOT NULL
+      AND UPPER(a.ticker) IN (
+          SELECT UPPER(accepted)
+          FROM jsonb_array_elements_text(COALESCE(sn.config -> 'accepted_assets', '[]'::jsonb)) accepted
+      )
+    ORDER BY n.chain_id, a.ticker
+"#;
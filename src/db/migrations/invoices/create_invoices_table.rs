@@ -0,0 +1,34 @@
+pub const CREATE_INVOICES_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoices (
+    id SERIAL PRIMARY KEY,
+    public_id VARCHAR(64) UNIQUE NOT NULL,
+    merchant_id INT NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    external_order_id VARCHAR(255),
+    currency VARCHAR(3) NOT NULL,
+    description TEXT,
+    subtotal NUMERIC(38,18) NOT NULL CHECK (subtotal >= 0),
+    tax_rate NUMERIC(9,6),
+    tax_amount NUMERIC(38,18) NOT NULL DEFAULT 0 CHECK (tax_amount >= 0),
+    total NUMERIC(38,18) NOT NULL CHECK (total > 0),
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(32) NOT NULL DEFAULT 'open',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    paid_at TIMESTAMPTZ,
+    UNIQUE (merchant_id, mode, external_order_id)
+)"#;
+
+pub const CREATE_INVOICE_LINE_ITEMS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS invoice_line_items (
+    id SERIAL PRIMARY KEY,
+    invoice_id INT NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    quantity NUMERIC(38,18) NOT NULL CHECK (quantity > 0),
+    unit_amount NUMERIC(38,18) NOT NULL CHECK (unit_amount >= 0),
+    amount NUMERIC(38,18) NOT NULL,
+    UNIQUE (invoice_id, position)
+)"#;
+
+pub const CREATE_INDEX_INVOICES_MERCHANT_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_invoices_merchant_id ON invoices (merchant_id, mode, id)"#;
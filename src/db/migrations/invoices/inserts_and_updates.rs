@@ -0,0 +1,21 @@
+pub const ADD_INVOICE: &str = r#"
+    INSERT INTO invoices
+        (public_id, merchant_id, mode, external_order_id, currency, description, subtotal,
+            tax_rate, tax_amount, total, metadata)
+    VALUES ($1, $2, $3, $4, $5, $6, $7::NUMERIC, $8::NUMERIC, $9::NUMERIC, $10::NUMERIC, $11)
+    ON CONFLICT (merchant_id, mode, external_order_id) DO NOTHING
+    RETURNING id
+"#;
+
+pub const ADD_INVOICE_LINE_ITEM: &str = r#"
+    INSERT INTO invoice_line_items
+        (invoice_id, position, description, quantity, unit_amount, amount)
+    VALUES ($1, $2, $3, $4::NUMERIC, $5::NUMERIC, $6::NUMERIC)
+"#;
+
+pub const SET_INVOICE_PAID: &str = r#"
+    UPDATE invoices
+    SET status = 'paid',
+        paid_at = CURRENT_TIMESTAMP
+    WHERE public_id = $1 AND status = 'open'
+"#;
@@ -0,0 +1,48 @@
+pub const GET_INVOICE: &str = r#"
+    SELECT i.id, i.public_id AS invoice_id, i.mode, i.external_order_id, i.currency,
+        i.description, i.subtotal, i.tax_rate, i.tax_amount, i.total, i.metadata, i.status,
+        COALESCE((
+            SELECT jsonb_agg(jsonb_build_object(
+                'description', li.description,
+                'quantity', li.quantity::TEXT,
+                'unit_amount', li.unit_amount::TEXT,
+                'amount', li.amount::TEXT
+            ) ORDER BY li.position)
+            FROM invoice_line_items li
+            WHERE li.invoice_id = i.id
+        ), '[]'::jsonb) AS line_items,
+        i.created_at, i.paid_at
+    FROM invoices i
+    WHERE i.public_id = $1 AND i.merchant_id = $2
+      AND ($3::VARCHAR IS NULL OR i.mode = $3)
+"#;
+
+pub const GET_INVOICES_FOR_MERCHANT: &str = r#"
+    SELECT i.id, i.public_id AS invoice_id, i.mode, i.external_order_id, i.currency,
+        i.description, i.subtotal, i.tax_rate, i.tax_amount, i.total, i.metadata, i.status,
+        COALESCE((
+            SELECT jsonb_agg(jsonb_build_object(
+                'description', li.description,
+                'quantity', li.quantity::TEXT,
+                'unit_amount', li.unit_amount::TEXT,
+                'amount', li.amount::TEXT
+            ) ORDER BY li.position)
+            FROM invoice_line_items li
+            WHERE li.invoice_id = i.id
+        ), '[]'::jsonb) AS line_items,
+        i.created_at, i.paid_at
+    FROM invoices i
+    WHERE i.merchant_id = $1
+      AND ($2::INT IS NULL OR i.id < $2)
+      AND ($3::VARCHAR IS NULL OR i.status = $3)
+      AND ($4::VARCHAR IS NULL OR i.external_order_id = $4)
+      AND i.mode = $5
+    ORDER BY i.id DESC
+    LIMIT $6
+"#;
+
+pub const GET_INVOICE_REFERENCE: &str = r#"
+    SELECT public_id AS invoice_id, external_order_id, metadata
+    FROM invoices
+    WHERE public_id = $1
+"#;
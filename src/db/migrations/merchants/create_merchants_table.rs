@@ -6,6 +6,13 @@ pub const CREATE_MERCHANT_TABLE: &str = r#"
     password_hash VARCHAR(255) NOT NULL,
     supported_networks JSONB,
     payments JSONB,
+    payment_expiry_minutes INT CHECK (payment_expiry_minutes > 0),
+    underpayment_tolerance NUMERIC(5,4) NOT NULL DEFAULT 0
+        CHECK (underpayment_tolerance >= 0 AND underpayment_tolerance < 1),
+    overpayment_tolerance NUMERIC(5,4) NOT NULL DEFAULT 0 CHECK (overpayment_tolerance >= 0),
+    webhook_secret VARCHAR(64) NOT NULL,
+    previous_webhook_secret VARCHAR(64),
+    previous_webhook_secret_expires_at TIMESTAMPTZ,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 )"#;
@@ -1,7 +1,7 @@
 pub const ADD_MERCHANT: &str = r#"
     INSERT INTO merchants 
-    (username, email, password_hash, supported_networks)
-    VALUES ($1, $2, $3, $4)
+    (username, email, password_hash, supported_networks, webhook_secret)
+    VALUES ($1, $2, $3, $4, $5)
     returning id;
 "#;
 
@@ -113,3 +113,27 @@ pub const UPDATE_NETWORK_ADDRESS_MERCHANT: &str = r#"
     WHERE id = $3
     RETURNING supported_networks;
 "#;
+
+pub const UPDATE_MERCHANT_PAYMENT_EXPIRY: &str = r#"
+    UPDATE merchants
+    SET payment_expiry_minutes = $2
+    WHERE id = $1
+    RETURNING id;
+"#;
+
+pub const UPDATE_MERCHANT_PAYMENT_TOLERANCE: &str = r#"
+    UPDATE merchants
+    SET underpayment_tolerance = $2::NUMERIC,
+        overpayment_tolerance = $3::NUMERIC
+    WHERE id = $1
+    RETURNING id;
+"#;
+
+pub const ROTATE_MERCHANT_WEBHOOK_SECRET: &str = r#"
+    UPDATE merchants
+    SET previous_webhook_secret = webhook_secret,
+        previous_webhook_secret_expires_at = CURRENT_TIMESTAMP + make_interval(mins => $3),
+        webhook_secret = $2
+    WHERE id = $1
+    RETURNING webhook_secret, previous_webhook_secret, previous_webhook_secret_expires_at;
+"#;
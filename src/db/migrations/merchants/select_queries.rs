@@ -17,3 +17,21 @@ pub const GET_MERCHANT_NETWORK_ADDRESS: &str = r#"
     FROM merchants
     WHERE id = $1;
 "#;
+
+pub const GET_MERCHANT_PAYMENT_EXPIRY: &str = r#"
+    SELECT payment_expiry_minutes
+    FROM merchants
+    WHERE id = $1;
+"#;
+
+pub const GET_MERCHANT_PAYMENT_TOLERANCE: &str = r#"
+    SELECT underpayment_tolerance, overpayment_tolerance
+    FROM merchants
+    WHERE id = $1;
+"#;
+
+pub const GET_MERCHANT_WEBHOOK_SECRETS: &str = r#"
+    SELECT webhook_secret, previous_webhook_secret, previous_webhook_secret_expires_at
+    FROM merchants
+    WHERE id = $1;
+"#;
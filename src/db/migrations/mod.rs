@@ -1,4 +1,9 @@
 pub mod admins;
+pub mod api_keys;
+pub mod checkout;
+pub mod invoices;
 pub mod merchants;
 pub mod networks;
-pub mod payments;
\ No newline at end of file
+pub mod payments;
+pub mod verification_jobs;
+pub mod webhooks;
\ No newline at end of file
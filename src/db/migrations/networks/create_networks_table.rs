@@ -3,9 +3,38 @@ pub const CREATE_NETWORK_TABLE: &str = r#"
     id SERIAL PRIMARY KEY,
     chain_id BIGINT UNIQUE NOT NULL,
     name VARCHAR(255) NOT NULL,
+    chain_family VARCHAR(32) NOT NULL DEFAULT 'evm',
+    chain_identifier VARCHAR(255) NOT NULL,
+    is_testnet BOOLEAN NOT NULL DEFAULT FALSE,
     rpc TEXT NOT NULL,
-    supported_assets JSONB,
+    rpc_endpoints JSONB NOT NULL DEFAULT '[]'::jsonb,
+    rpc_quorum INT NOT NULL DEFAULT 1,
+    confirmation_mode VARCHAR(32) NOT NULL DEFAULT 'depth',
+    confirmation_depth BIGINT NOT NULL DEFAULT 12,
     last_updated_by VARCHAR(255),
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 )"#;
+
+pub const CREATE_NETWORK_ASSETS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS network_assets (
+    id SERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL REFERENCES networks(chain_id) ON DELETE CASCADE,
+    ticker VARCHAR(32) NOT NULL,
+    address VARCHAR(255) NOT NULL,
+    decimals SMALLINT NOT NULL CHECK (decimals >= 0),
+    symbol VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_updated_by VARCHAR(255),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (chain_id, ticker),
+    UNIQUE (chain_id, address)
+)"#;
+
+pub const CREATE_WATCHER_CHECKPOINTS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS watcher_checkpoints (
+    chain_id BIGINT PRIMARY KEY REFERENCES networks(chain_id) ON DELETE CASCADE,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)"#;
@@ -1,19 +1,54 @@
 pub const ADD_NETWORK: &str = r#"
     INSERT INTO networks 
-    (chain_id, name, rpc, supported_assets, last_updated_by)
-    VALUES ($1, $2, $3, $4, $5)
+    (chain_id, name, chain_family, chain_identifier, rpc, rpc_endpoints, rpc_quorum,
+        confirmation_mode, confirmation_depth, last_updated_by, is_testnet)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
     returning id;
 "#;
 
 pub const ADD_ASSET: &str = r#"
+    INSERT INTO network_assets
+    (chain_id, ticker, address, decimals, symbol, last_updated_by)
+    VALUES ($1, $2, $3, $4, $5, $6)
+    ON CONFLICT (chain_id, ticker) DO UPDATE
+    SET address = $3,
+        decimals = $4,
+        symbol = $5,
+        enabled = TRUE,
+        last_updated_by = $6
+"#;
+
+pub const SET_ASSET_ENABLED: &str = r#"
+    UPDATE network_assets
+    SET enabled = $3,
+        last_updated_by = $4
+    WHERE chain_id = $1 AND ticker = $2
+    RETURNING id;
+"#;
+
+pub const UPDATE_CONFIRMATION_POLICY: &str = r#"
     UPDATE networks
-    SET supported_assets = 
-        CASE
-            WHEN supported_assets IS NULL THEN
-                '{"$1": "$2"}'::jsonb
-            ELSE
-                supported_assets || jsonb_build_object($1, $2)
-        END,
+    SET confirmation_mode = $2,
+        confirmation_depth = $3,
         last_updated_by = $4
-    WHERE chain_id = $3;
+    WHERE chain_id = $1
+    RETURNING id;
+"#;
+
+pub const UPDATE_RPC_ENDPOINTS: &str = r#"
+    UPDATE networks
+    SET rpc = $2,
+        rpc_endpoints = $3,
+        rpc_quorum = $4,
+        last_updated_by = $5
+    WHERE chain_id = $1
+    RETURNING id;
+"#;
+
+pub const UPSERT_WATCHER_CHECKPOINT: &str = r#"
+    INSERT INTO watcher_checkpoints (chain_id, last_block)
+    VALUES ($1, $2)
+    ON CONFLICT (chain_id) DO UPDATE
+    SET last_block = $2,
+        updated_at = CURRENT_TIMESTAMP
 "#;
@@ -1,6 +1,17 @@
 pub const GET_NETWORK_ASSETS: &str = r#"
-    SELECT supported_assets FROM networks
+    SELECT id, chain_id, ticker, address, decimals, symbol, enabled FROM network_assets
     WHERE chain_id = $1
+    ORDER BY ticker
+"#;
+
+pub const GET_ALL_NETWORK_ASSETS: &str = r#"
+    SELECT id, chain_id, ticker, address, decimals, symbol, enabled FROM network_assets
+    ORDER BY chain_id, ticker
+"#;
+
+pub const GET_NETWORK_ASSET: &str = r#"
+    SELECT id, chain_id, ticker, address, decimals, symbol, enabled FROM network_assets
+    WHERE chain_id = $1 AND ticker = $2
 "#;
 
 pub const GET_NETWORK: &str = r#"
@@ -15,14 +26,14 @@ pub const GET_ALL_NETWORKS: &str = r#"
 pub const CHECK_NETWORK_SUPPORTED_ASSET: &str = r#"
     SELECT EXISTS(
         SELECT 1
-        FROM networks
+        FROM network_assets
         WHERE chain_id = $1
-          AND supported_assets ? $2
+          AND ticker = $2
+          AND enabled
     )
 "#;
 
-pub const GET_NETWORK_AND_ASSETS: &str = r#"
-    SELECT name, supported_assets
-    FROM networks
+pub const GET_WATCHER_CHECKPOINT: &str = r#"
+    SELECT last_block FROM watcher_checkpoints
     WHERE chain_id = $1
 "#;
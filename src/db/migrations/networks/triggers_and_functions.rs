@@ -14,3 +14,10 @@ pub const TRIGGER_FUNCTION: &str = r#"
     FOR EACH ROW
     EXECUTE FUNCTION update_updated_at_column();
 "#;
+
+pub const TRIGGER_FUNCTION_NETWORK_ASSETS: &str = r#"
+    CREATE TRIGGER set_updated_at_network_assets
+    BEFORE UPDATE ON network_assets
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+"#;
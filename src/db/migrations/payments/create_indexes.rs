@@ -4,3 +4,13 @@ pub const CREATE_INDEX_TX_HASH: &str = r#"
     CREATE INDEX idx_payments_tx_hash ON payments (tx_hash)"#;
 pub const CREATE_INDEX_NETWORK: &str = r#"
     CREATE INDEX idx_payments_network ON payments (network)"#;
+pub const CREATE_INDEX_PENDING_STATUS: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_pending_payments_status ON pending_payments (status)"#;
+pub const CREATE_INDEX_PENDING_EXPIRES_AT: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_pending_payments_expires_at ON pending_payments (expires_at)
+    WHERE status IN ('created', 'underpaid', 'failed')"#;
+pub const CREATE_INDEX_PAYMENT_TRANSFERS_PENDING_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_payment_transfers_pending_payment_id
+    ON payment_transfers (pending_payment_id)"#;
+pub const CREATE_INDEX_INVOICE_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_payments_invoice_id ON payments (invoice_id)"#;
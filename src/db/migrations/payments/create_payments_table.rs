@@ -1,12 +1,16 @@
 pub const CREATE_PAYMENTS_TABLE: &str = r#"
     CREATE TABLE IF NOT EXISTS payments (
     id SERIAL PRIMARY KEY,
+    pending_payment_id INT UNIQUE REFERENCES pending_payments(id) ON DELETE SET NULL,
     merchant_id INT REFERENCES merchants(id) ON DELETE CASCADE,
     sender VARCHAR(255) NOT NULL,
-    amount NUMERIC(28,8) NOT NULL CHECK (amount > 0),
+    amount NUMERIC(38,18) NOT NULL CHECK (amount > 0),
+    amount_received NUMERIC(38,18) NOT NULL,
     tx_hash VARCHAR(255) UNIQUE NOT NULL,
     asset VARCHAR(255) NOT NULL,
     network VARCHAR(255) NOT NULL,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    invoice_id VARCHAR(64) REFERENCES invoices(public_id) ON DELETE SET NULL,
     time TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 )"#;
 
@@ -15,9 +19,30 @@ pub const CREATE_PENDING_PAYMENTS_TABLE: &str = r#"
     id SERIAL PRIMARY KEY,
     merchant_id INT REFERENCES merchants(id) ON DELETE CASCADE,
     sender VARCHAR(255) NOT NULL,
-    amount NUMERIC(28,8) NOT NULL CHECK (amount > 0),
+    amount NUMERIC(38,18) NOT NULL CHECK (amount > 0),
+    amount_received NUMERIC(38,18) NOT NULL DEFAULT 0,
     asset VARCHAR(255) NOT NULL,
     network VARCHAR(255) NOT NULL,
-    webhook_url TEXT NOT NULL,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    invoice_id VARCHAR(64) REFERENCES invoices(public_id) ON DELETE SET NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'created',
+    tx_hash VARCHAR(255),
+    block_number BIGINT,
+    block_hash VARCHAR(255),
+    confirmations BIGINT NOT NULL DEFAULT 0,
+    failure_reason TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    time TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)"#;
+
+pub const CREATE_PAYMENT_TRANSFERS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS payment_transfers (
+    id SERIAL PRIMARY KEY,
+    pending_payment_id INT NOT NULL REFERENCES pending_payments(id) ON DELETE CASCADE,
+    tx_hash VARCHAR(255) UNIQUE NOT NULL,
+    amount NUMERIC(38,18) NOT NULL CHECK (amount > 0),
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(255) NOT NULL,
     time TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 )"#;
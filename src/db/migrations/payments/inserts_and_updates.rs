@@ -1,20 +1,153 @@
 pub const ADD_PAYMENT: &str = r#"
     INSERT INTO payments 
-        (merchant_id, sender, amount, tx_hash, asset, network)
+        (merchant_id, sender, amount, tx_hash, asset, network, amount_received, pending_payment_id,
+            mode, invoice_id)
     VALUES 
-        ($1, $2, $3::NUMERIC, $4, $5, $6)
+        ($1, $2, $3::NUMERIC, $4, $5, $6, $7::NUMERIC, $8, $9, $10)
+    ON CONFLICT DO NOTHING
     returning id
 "#;
 
 pub const ADD_PENDING_PAYMENT: &str = r#"
     INSERT INTO pending_payments 
-        (merchant_id, sender, amount, asset, network, webhook_url)
+        (merchant_id, sender, amount, asset, network, expires_at, mode, invoice_id)
     VALUES 
-        ($1, $2, $3::NUMERIC, $4, $5, $6)
+        ($1, $2, $3::NUMERIC, $4, $5, $6, $7, $8)
     returning id
 "#;
 
-pub const DELETE_PENDING_PAYMENT: &str = r#"
+pub const _DELETE_PENDING_PAYMENT: &str = r#"
     DELETE FROM pending_payments
     WHERE id = $1
 "#;
+
+pub const SET_PENDING_PAYMENT_SEEN: &str = r#"
+    UPDATE pending_payments
+    SET status = 'seen',
+        tx_hash = $2,
+        block_number = $3,
+        block_hash = $4,
+        confirmations = 0,
+        failure_reason = NULL
+    WHERE id = $1
+      AND status IN ('created', 'underpaid', 'reorged', 'failed')
+      AND (expires_at > CURRENT_TIMESTAMP
+        OR (status = 'reorged'
+          AND GREATEST(expires_at, updated_at) + INTERVAL '1 hour' > CURRENT_TIMESTAMP))
+    RETURNING id
+"#;
+
+pub const SET_PENDING_PAYMENT_UNDERPAID: &str = r#"
+    UPDATE pending_payments
+    SET status = 'underpaid',
+        tx_hash = $2,
+        block_number = NULL,
+        block_hash = NULL,
+        confirmations = 0,
+        failure_reason = NULL
+    WHERE id = $1
+      AND status IN ('created', 'underpaid', 'reorged', 'failed')
+    RETURNING id
+"#;
+
+pub const ADD_PENDING_PAYMENT_RECEIVED: &str = r#"
+    UPDATE pending_payments
+    SET amount_received = amount_received + $2::NUMERIC
+    WHERE id = $1
+      AND (status IN ('seen', 'confirming')
+        OR (status IN ('created', 'underpaid', 'reorged', 'failed')
+          AND (expires_at > CURRENT_TIMESTAMP
+            OR (status = 'reorged'
+              AND GREATEST(expires_at, updated_at) + INTERVAL '1 hour' > CURRENT_TIMESTAMP))))
+    RETURNING amount_received
+"#;
+
+pub const ADD_PAYMENT_TRANSFER: &str = r#"
+    INSERT INTO payment_transfers
+        (pending_payment_id, tx_hash, amount, block_number, block_hash)
+    VALUES
+        ($1, $2, $3::NUMERIC, $4, $5)
+    ON CONFLICT (tx_hash) DO NOTHING
+    RETURNING id
+"#;
+
+pub const UPDATE_PAYMENT_TRANSFER_INCLUSION: &str = r#"
+    UPDATE payment_transfers
+    SET block_number = $3,
+        block_hash = $4
+    WHERE pending_payment_id = $1
+      AND tx_hash = $2
+"#;
+
+pub const REMOVE_PAYMENT_TRANSFER: &str = r#"
+    WITH removed AS (
+        DELETE FROM payment_transfers
+        WHERE pending_payment_id = $1
+          AND tx_hash = $2
+        RETURNING amount
+    )
+    UPDATE pending_payments
+    SET amount_received = amount_received - COALESCE((SELECT SUM(amount) FROM removed), 0)
+    WHERE id = $1
+    RETURNING amount_received
+"#;
+
+pub const SET_PENDING_PAYMENT_CONFIRMING: &str = r#"
+    UPDATE pending_payments
+    SET status = 'confirming',
+        confirmations = $2
+    WHERE id = $1
+      AND status IN ('seen', 'confirming')
+    RETURNING id
+"#;
+
+pub const SET_PENDING_PAYMENT_CONFIRMED: &str = r#"
+    UPDATE pending_payments
+    SET status = $3,
+        confirmations = $2
+    WHERE id = $1
+      AND status IN ('seen', 'confirming')
+    RETURNING id
+"#;
+
+pub const SET_PENDING_PAYMENT_REORGED: &str = r#"
+    UPDATE pending_payments
+    SET status = 'reorged',
+        block_number = NULL,
+        block_hash = NULL,
+        confirmations = 0
+    WHERE id = $1
+      AND status IN ('seen', 'confirming')
+    RETURNING id
+"#;
+
+pub const SET_PENDING_PAYMENT_FAILED: &str = r#"
+    UPDATE pending_payments
+    SET status = 'failed',
+        tx_hash = $2,
+        failure_reason = $3
+    WHERE id = $1
+      AND status IN ('created', 'reorged')
+    RETURNING id
+"#;
+
+pub const EXPIRE_PENDING_PAYMENTS: &str = r#"
+    UPDATE pending_payments
+    SET status = 'expired'
+    WHERE (status IN ('created', 'underpaid', 'failed') AND expires_at <= CURRENT_TIMESTAMP)
+      OR (status = 'reorged'
+        AND GREATEST(expires_at, updated_at) + INTERVAL '1 hour' <= CURRENT_TIMESTAMP)
+    RETURNING id, merchant_id, sender, amount, amount_received, asset, network, mode, invoice_id,
+        status, tx_hash, block_number, block_hash, confirmations, failure_reason, expires_at, time
+"#;
+
+pub const EXPIRE_PENDING_PAYMENT: &str = r#"
+    UPDATE pending_payments
+    SET status = 'expired'
+    WHERE id = $1
+      AND status IN ('created', 'failed')
+"#;
+
+pub const NOTIFY_PAYMENT_STATUS: &str = r#"
+    SELECT pg_notify($1, $2)
+"#;
@@ -1,4 +1,5 @@
 pub mod create_indexes;
 pub mod create_payments_table;
 pub mod inserts_and_updates;
-pub mod select_queries;
\ No newline at end of file
+pub mod select_queries;
+pub mod triggers;
@@ -1,40 +1,69 @@
-pub const _GET_PAYMENTS_FOR_MERCHANT: &str = r#"
-    SELECT id, sender, amount, tx_hash, asset, network, time 
-    FROM payments
-    WHERE merchant_id = $1
-    ORDER BY time DESC
-    LIMIT $2 OFFSET $3
+pub const GET_PAYMENTS_FOR_MERCHANT: &str = r#"
+    SELECT pay.id, pay.pending_payment_id, pay.sender, pay.amount, pay.amount_received,
+        pay.tx_hash, pay.asset, pay.network, pay.mode, COALESCE(pp.status, 'confirmed') AS status,
+        pay.invoice_id, inv.external_order_id, pay.time
+    FROM payments pay
+    LEFT JOIN pending_payments pp ON pp.id = pay.pending_payment_id
+    LEFT JOIN invoices inv ON inv.public_id = pay.invoice_id
+    WHERE pay.merchant_id = $1
+      AND ($2::INT IS NULL OR pay.id < $2)
+      AND ($3::VARCHAR IS NULL OR pay.network = $3)
+      AND ($4::VARCHAR IS NULL OR pay.asset = $4)
+      AND ($5::VARCHAR IS NULL OR COALESCE(pp.status, 'confirmed') = $5)
+      AND ($6::VARCHAR IS NULL OR LOWER(pay.sender) = LOWER($6))
+      AND ($7::TIMESTAMPTZ IS NULL OR pay.time >= $7)
+      AND ($8::TIMESTAMPTZ IS NULL OR pay.time < $8)
+      AND pay.mode = $10
+      AND ($11::VARCHAR IS NULL OR pay.invoice_id = $11)
+      AND ($12::VARCHAR IS NULL OR inv.external_order_id = $12)
+    ORDER BY pay.id DESC
+    LIMIT $9
 "#;
 
 pub const GET_PENDING_PAYMENT: &str = r#"
-    SELECT id, merchant_id, sender, amount, asset, network, webhook_url, time 
+    SELECT id, merchant_id, sender, amount, amount_received, asset, network, mode, invoice_id,
+        status, tx_hash, block_number, block_hash, confirmations, failure_reason, expires_at, time 
     FROM pending_payments
     WHERE id = $1
 "#;
 
-pub const _GET_PAYMENT_BY_TX_HASH: &str = r#"
-    SELECT id, merchant_id, sender, amount, asset, network, time 
-    FROM payments
-    WHERE tx_hash = $1
+pub const GET_PAYMENT_BY_TX_HASH: &str = r#"
+    SELECT pay.id, pay.pending_payment_id, pay.sender, pay.amount, pay.amount_received,
+        pay.tx_hash, pay.asset, pay.network, pay.mode, COALESCE(pp.status, 'confirmed') AS status,
+        pay.invoice_id, inv.external_order_id, pay.time
+    FROM payments pay
+    LEFT JOIN pending_payments pp ON pp.id = pay.pending_payment_id
+    LEFT JOIN invoices inv ON inv.public_id = pay.invoice_id
+    WHERE pay.tx_hash = $1 AND pay.merchant_id = $2
+      AND ($3::VARCHAR IS NULL OR pay.mode = $3)
 "#;
 
-pub const _COUNT_PAYMENTS_FOR_MERCHANT: &str = r#"
+pub const COUNT_PAYMENTS_FOR_MERCHANT: &str = r#"
     SELECT COUNT(*)
-    FROM payments
-    WHERE merchant_id = $1
+    FROM payments pay
+    LEFT JOIN pending_payments pp ON pp.id = pay.pending_payment_id
+    LEFT JOIN invoices inv ON inv.public_id = pay.invoice_id
+    WHERE pay.merchant_id = $1
+      AND ($2::VARCHAR IS NULL OR pay.network = $2)
+      AND ($3::VARCHAR IS NULL OR pay.asset = $3)
+      AND ($4::VARCHAR IS NULL OR COALESCE(pp.status, 'confirmed') = $4)
+      AND ($5::VARCHAR IS NULL OR LOWER(pay.sender) = LOWER($5))
+      AND ($6::TIMESTAMPTZ IS NULL OR pay.time >= $6)
+      AND ($7::TIMESTAMPTZ IS NULL OR pay.time < $7)
+      AND pay.mode = $8
+      AND ($9::VARCHAR IS NULL OR pay.invoice_id = $9)
+      AND ($10::VARCHAR IS NULL OR inv.external_order_id = $10)
 "#;
 
-pub const _SEARCH_MERCHANT_PAYMENTS_BY_SENDER: &str = r#"
-    SELECT id, amount, tx_hash, asset, network, time 
-    FROM payments
-    WHERE merchant_id = $1 AND sender ILIKE $2
-    ORDER BY time DESC
-"#;
-
-pub const _GET_PAYMENT_BY_ID: &str = r#"
-    SELECT id, merchant_id, sender, amount, tx_hash, asset, network, time 
-    FROM payments
-    WHERE id = $1
+pub const GET_PAYMENT_BY_ID: &str = r#"
+    SELECT pay.id, pay.pending_payment_id, pay.sender, pay.amount, pay.amount_received,
+        pay.tx_hash, pay.asset, pay.network, pay.mode, COALESCE(pp.status, 'confirmed') AS status,
+        pay.invoice_id, inv.external_order_id, pay.time
+    FROM payments pay
+    LEFT JOIN pending_payments pp ON pp.id = pay.pending_payment_id
+    LEFT JOIN invoices inv ON inv.public_id = pay.invoice_id
+    WHERE pay.id = $1 AND pay.merchant_id = $2
+      AND ($3::VARCHAR IS NULL OR pay.mode = $3)
 "#;
 
 pub const _AGGREGATE_PAYMENTS: &str = r#"
@@ -48,3 +77,55 @@ pub const GET_PAYMENT_EXISTENCE_BY_HASH: &str = r#"
         SELECT 1 FROM payments WHERE tx_hash = $1
     )
 "#;
+
+pub const GET_PENDING_PAYMENTS_FOR_NETWORK: &str = r#"
+    SELECT p.id, p.sender, p.asset,
+        m.supported_networks -> $2::text ->> 'address' AS merchant_address
+    FROM pending_payments p
+    JOIN merchants m ON m.id = p.merchant_id
+    WHERE p.network = $1
+      AND (p.status IN ('seen', 'confirming')
+        OR (p.status IN ('created', 'underpaid', 'reorged', 'failed')
+          AND (p.expires_at > CURRENT_TIMESTAMP
+            OR (p.status = 'reorged'
+              AND GREATEST(p.expires_at, p.updated_at) + INTERVAL '1 hour' > CURRENT_TIMESTAMP))))
+"#;
+
+pub const GET_PENDING_PAYMENTS_AWAITING_CONFIRMATION: &str = r#"
+    SELECT p.id, p.merchant_id, p.sender, p.amount, p.amount_received, p.asset, p.network,
+        p.mode, p.invoice_id, p.status, p.tx_hash, p.block_number, p.block_hash, p.confirmations, p.failure_reason, p.expires_at,
+        p.time,
+        n.chain_id
+    FROM pending_payments p
+    JOIN networks n ON n.name = p.network
+    WHERE p.status IN ('seen', 'confirming')
+"#;
+
+pub const GET_PAYMENT_TRANSFER_OWNER: &str = r#"
+    SELECT pending_payment_id
+    FROM payment_transfers
+    WHERE tx_hash = $1
+"#;
+
+pub const GET_PAYMENT_TRANSFERS: &str = r#"
+    SELECT tx_hash, block_number, block_hash
+    FROM payment_transfers
+    WHERE pending_payment_id = $1
+    ORDER BY id
+"#;
+
+pub const LOCK_PENDING_PAYMENT: &str = r#"
+    SELECT id, merchant_id, sender, amount, amount_received, asset, network, mode, invoice_id,
+        status, tx_hash, block_number, block_hash, confirmations, failure_reason, expires_at, time
+    FROM pending_payments
+    WHERE id = $1
+    FOR UPDATE
+"#;
+
+pub const GET_PAYMENT_FOR_PENDING_OR_TX: &str = r#"
+    SELECT id, pending_payment_id
+    FROM payments
+    WHERE pending_payment_id = $1 OR tx_hash = $2
+    ORDER BY (pending_payment_id = $1) DESC NULLS LAST
+    LIMIT 1
+"#;
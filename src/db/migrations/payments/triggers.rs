@@ -0,0 +1,6 @@
+pub const TRIGGER_FUNCTION_PENDING_PAYMENTS: &str = r#" 
+    CREATE TRIGGER set_updated_at_pending_payments
+    BEFORE UPDATE ON pending_payments
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+"#;
@@ -0,0 +1,16 @@
+pub const CREATE_VERIFICATION_JOBS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS verification_jobs (
+        id SERIAL PRIMARY KEY,
+        message_id VARCHAR(64) NOT NULL UNIQUE,
+        payload JSONB NOT NULL,
+        attempts INT NOT NULL DEFAULT 0,
+        last_error TEXT,
+        run_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        dead_lettered_at TIMESTAMPTZ,
+        time TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    )
+"#;
+
+pub const CREATE_INDEX_VERIFICATION_JOBS_RUN_AT: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_verification_jobs_run_at ON verification_jobs (run_at)
+    WHERE dead_lettered_at IS NULL"#;
@@ -0,0 +1,35 @@
+pub const ADD_VERIFICATION_JOB: &str = r#"
+    INSERT INTO verification_jobs (message_id, payload)
+    VALUES ($1, $2::JSONB)
+"#;
+
+pub const DELETE_VERIFICATION_JOB: &str = r#"
+    DELETE FROM verification_jobs
+    WHERE id = $1
+"#;
+
+pub const RETRY_VERIFICATION_JOB: &str = r#"
+    UPDATE verification_jobs
+    SET attempts = $2,
+        last_error = $3,
+        run_at = CURRENT_TIMESTAMP + make_interval(secs => $4)
+    WHERE id = $1
+"#;
+
+pub const DEAD_LETTER_VERIFICATION_JOB: &str = r#"
+    UPDATE verification_jobs
+    SET attempts = $2,
+        last_error = $3,
+        dead_lettered_at = CURRENT_TIMESTAMP
+    WHERE id = $1
+"#;
+
+pub const REPLAY_VERIFICATION_JOBS: &str = r#"
+    UPDATE verification_jobs
+    SET attempts = 0,
+        last_error = NULL,
+        dead_lettered_at = NULL,
+        run_at = CURRENT_TIMESTAMP
+    WHERE dead_lettered_at IS NOT NULL
+        AND ($1::VARCHAR IS NULL OR message_id = $1)
+"#;
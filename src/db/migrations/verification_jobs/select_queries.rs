@@ -0,0 +1,21 @@
+pub const CLAIM_VERIFICATION_JOB: &str = r#"
+    UPDATE verification_jobs
+    SET run_at = CURRENT_TIMESTAMP + make_interval(secs => $1)
+    WHERE id = (
+        SELECT id
+        FROM verification_jobs
+        WHERE dead_lettered_at IS NULL AND run_at <= CURRENT_TIMESTAMP
+        ORDER BY run_at
+        LIMIT 1
+        FOR UPDATE SKIP LOCKED
+    )
+    RETURNING id, message_id, payload::TEXT AS payload, attempts, last_error, dead_lettered_at
+"#;
+
+pub const GET_DEAD_LETTERED_VERIFICATION_JOBS: &str = r#"
+    SELECT id, message_id, payload::TEXT AS payload, attempts, last_error, dead_lettered_at
+    FROM verification_jobs
+    WHERE dead_lettered_at IS NOT NULL
+    ORDER BY dead_lettered_at
+    LIMIT $1
+"#;
@@ -0,0 +1,55 @@
+pub const CREATE_WEBHOOK_ENDPOINTS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS webhook_endpoints (
+    id SERIAL PRIMARY KEY,
+    merchant_id INT NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    url TEXT NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (merchant_id, mode, url)
+)"#;
+
+pub const CREATE_WEBHOOK_EVENTS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS webhook_events (
+    id SERIAL PRIMARY KEY,
+    merchant_id INT NOT NULL REFERENCES merchants(id) ON DELETE CASCADE,
+    mode VARCHAR(8) NOT NULL DEFAULT 'live',
+    event_type VARCHAR(64) NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+)"#;
+
+pub const CREATE_WEBHOOK_DELIVERIES_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    webhook_event_id INT NOT NULL REFERENCES webhook_events(id) ON DELETE CASCADE,
+    webhook_endpoint_id INT NOT NULL REFERENCES webhook_endpoints(id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_status_code INT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+)"#;
+
+pub const CREATE_WEBHOOK_DELIVERY_ATTEMPTS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+    id SERIAL PRIMARY KEY,
+    webhook_delivery_id INT NOT NULL REFERENCES webhook_deliveries(id) ON DELETE CASCADE,
+    attempt INT NOT NULL,
+    status_code INT,
+    latency_ms INT NOT NULL,
+    response_body TEXT,
+    error TEXT,
+    time TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+)"#;
+
+pub const CREATE_INDEX_WEBHOOK_DELIVERIES_DUE: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending'"#;
+
+pub const CREATE_INDEX_WEBHOOK_DELIVERY_ATTEMPTS_DELIVERY_ID: &str = r#"
+    CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery_id
+    ON webhook_delivery_attempts (webhook_delivery_id)"#;
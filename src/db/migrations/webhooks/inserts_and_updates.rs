@@ -0,0 +1,80 @@
+pub const ADD_WEBHOOK_ENDPOINT: &str = r#"
+    INSERT INTO webhook_endpoints (merchant_id, url, event_types, mode)
+    VALUES ($1, $2, $3, $4)
+    RETURNING id, mode, url, event_types, enabled, created_at, updated_at
+"#;
+
+pub const UPDATE_WEBHOOK_ENDPOINT: &str = r#"
+    UPDATE webhook_endpoints
+    SET url = COALESCE($3, url),
+        event_types = COALESCE($4, event_types),
+        enabled = COALESCE($5, enabled),
+        updated_at = CURRENT_TIMESTAMP
+    WHERE id = $1 AND merchant_id = $2
+    RETURNING id, mode, url, event_types, enabled, created_at, updated_at
+"#;
+
+pub const REMOVE_WEBHOOK_ENDPOINT: &str = r#"
+    DELETE FROM webhook_endpoints
+    WHERE id = $1 AND merchant_id = $2
+    RETURNING id
+"#;
+
+pub const ADD_WEBHOOK_EVENT: &str = r#"
+    WITH event AS (
+        INSERT INTO webhook_events (merchant_id, event_type, payload, mode)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+    )
+    INSERT INTO webhook_deliveries (webhook_event_id, webhook_endpoint_id)
+    SELECT event.id, endpoint.id
+    FROM event, webhook_endpoints endpoint
+    WHERE endpoint.merchant_id = $1
+      AND endpoint.mode = $4
+      AND endpoint.enabled
+      AND (cardinality(endpoint.event_types) = 0 OR $2 = ANY(endpoint.event_types))
+"#;
+
+pub const CLAIM_DUE_WEBHOOK_DELIVERIES: &str = r#"
+    UPDATE webhook_deliveries d
+    SET next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $2)
+    FROM webhook_events e, webhook_endpoints w
+    WHERE d.id IN (
+        SELECT id
+        FROM webhook_deliveries
+        WHERE status = 'pending' AND next_attempt_at <= CURRENT_TIMESTAMP
+        ORDER BY next_attempt_at
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+    )
+      AND e.id = d.webhook_event_id
+      AND w.id = d.webhook_endpoint_id
+    RETURNING d.id, d.attempts, e.merchant_id, e.payload, w.url
+"#;
+
+pub const ADD_WEBHOOK_DELIVERY_ATTEMPT: &str = r#"
+    INSERT INTO webhook_delivery_attempts
+        (webhook_delivery_id, attempt, status_code, latency_ms, response_body, error)
+    VALUES ($1, $2, $3, $4, $5, $6)
+"#;
+
+pub const UPDATE_WEBHOOK_DELIVERY_RESULT: &str = r#"
+    UPDATE webhook_deliveries
+    SET status = $2,
+        attempts = $3,
+        last_status_code = $4,
+        next_attempt_at = CURRENT_TIMESTAMP + make_interval(secs => $5),
+        delivered_at = CASE WHEN $2 = 'succeeded' THEN CURRENT_TIMESTAMP ELSE delivered_at END
+    WHERE id = $1
+"#;
+
+pub const REDELIVER_WEBHOOK: &str = r#"
+    UPDATE webhook_deliveries d
+    SET status = 'pending',
+        next_attempt_at = CURRENT_TIMESTAMP
+    FROM webhook_events e
+    WHERE d.id = $1
+      AND e.id = d.webhook_event_id
+      AND e.merchant_id = $2
+    RETURNING d.id
+"#;
@@ -0,0 +1,3 @@
+pub mod create_webhooks_table;
+pub mod inserts_and_updates;
+pub mod select_queries;
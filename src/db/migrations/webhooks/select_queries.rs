@@ -0,0 +1,43 @@
+pub const GET_MERCHANT_WEBHOOK_ENDPOINTS: &str = r#"
+    SELECT id, mode, url, event_types, enabled, created_at, updated_at
+    FROM webhook_endpoints
+    WHERE merchant_id = $1
+    ORDER BY id
+"#;
+
+pub const COUNT_MERCHANT_WEBHOOK_ENDPOINTS: &str = r#"
+    SELECT COUNT(*)
+    FROM webhook_endpoints
+    WHERE merchant_id = $1
+"#;
+
+pub const GET_MERCHANT_WEBHOOK_DELIVERIES: &str = r#"
+    SELECT d.id, d.webhook_event_id, d.webhook_endpoint_id, e.mode, w.url, e.event_type, d.status,
+        d.attempts, d.last_status_code, d.next_attempt_at, d.delivered_at, d.created_at
+    FROM webhook_deliveries d
+    JOIN webhook_events e ON e.id = d.webhook_event_id
+    JOIN webhook_endpoints w ON w.id = d.webhook_endpoint_id
+    WHERE e.merchant_id = $1
+      AND ($2::INT IS NULL OR d.webhook_endpoint_id = $2)
+      AND ($3::VARCHAR IS NULL OR d.status = $3)
+      AND ($6::VARCHAR IS NULL OR e.mode = $6)
+    ORDER BY d.created_at DESC, d.id DESC
+    LIMIT $4 OFFSET $5
+"#;
+
+pub const GET_MERCHANT_WEBHOOK_DELIVERY: &str = r#"
+    SELECT d.id, d.webhook_event_id, d.webhook_endpoint_id, e.mode, w.url, e.event_type, d.status,
+        d.attempts, d.last_status_code, d.next_attempt_at, d.delivered_at, d.created_at,
+        e.payload
+    FROM webhook_deliveries d
+    JOIN webhook_events e ON e.id = d.webhook_event_id
+    JOIN webhook_endpoints w ON w.id = d.webhook_endpoint_id
+    WHERE d.id = $1 AND e.merchant_id = $2
+"#;
+
+pub const GET_WEBHOOK_DELIVERY_ATTEMPTS: &str = r#"
+    SELECT attempt, status_code, latency_ms, response_body, error, time
+    FROM webhook_delivery_attempts
+    WHERE webhook_delivery_id = $1
+    ORDER BY attempt
+"#;
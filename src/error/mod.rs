@@ -4,6 +4,7 @@ use alloy::{
     transports::{RpcError, TransportErrorKind},
 };
 use lettre::{address::AddressError, transport::smtp::Error as SmtpTransportError};
+use solana_client::client_error::ClientError;
 use std::{
     env::VarError,
     fmt::{self},
@@ -24,9 +25,13 @@ pub enum StabuseError {
     Forbidden(String),
     Unauthorized(String),
     Internal(String),
+    /// An RPC endpoint could not be reached or misbehaved. The only error
+    /// `with_failover` retries on another endpoint.
+    Rpc(String),
     EmailError(String),
     SmtpError(String),
     EnvError(String),
+    ServiceUnavailable(String),
 }
 
 impl fmt::Display for StabuseError {
@@ -46,9 +51,11 @@ impl fmt::Display for StabuseError {
             StabuseError::Forbidden(msg) => write!(f, "Fobidden: {}", msg),
             StabuseError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             StabuseError::Internal(msg) => write!(f, "Internal: {}", msg),
+            StabuseError::Rpc(msg) => write!(f, "RPC error: {}", msg),
             StabuseError::EmailError(msg) => write!(f, "Error sending mail: {}", msg),
             StabuseError::SmtpError(msg) => write!(f, "Error sending mail: {}", msg),
             StabuseError::EnvError(msg) => write!(f, "Error reading from env: {}", msg),
+            StabuseError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
             StabuseError::StdError(e) => write!(f, "{}", e),
         }
     }
@@ -92,7 +99,13 @@ impl From<VarError> for StabuseError {
 
 impl From<RpcError<TransportErrorKind>> for StabuseError {
     fn from(err: RpcError<TransportErrorKind>) -> Self {
-        StabuseError::Internal(err.to_string())
+        StabuseError::Rpc(err.to_string())
+    }
+}
+
+impl From<ClientError> for StabuseError {
+    fn from(err: ClientError) -> Self {
+        StabuseError::Rpc(err.to_string())
     }
 }
 
@@ -114,6 +127,12 @@ impl From<serde_json::Error> for StabuseError {
     }
 }
 
+impl From<lapin::Error> for StabuseError {
+    fn from(error: lapin::Error) -> Self {
+        StabuseError::ServiceUnavailable(format!("Message broker error: {}", error))
+    }
+}
+
 impl From<Box<dyn std::error::Error + Send + Sync>> for StabuseError {
     fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
         StabuseError::EnvError(error.to_string())
@@ -176,6 +195,12 @@ impl ResponseError for StabuseError {
             StabuseError::Internal(msg) => {
                 HttpResponse::NotFound().json(serde_json::json!({"error": msg.to_string()}))
             }
+            StabuseError::Rpc(msg) => {
+                HttpResponse::BadGateway().json(serde_json::json!({"error": msg.to_string()}))
+            }
+            StabuseError::ServiceUnavailable(msg) => HttpResponse::ServiceUnavailable()
+                .insert_header(("Retry-After", "5"))
+                .json(serde_json::json!({"error": msg.to_string()})),
         }
     }
 
@@ -197,6 +222,8 @@ impl ResponseError for StabuseError {
             StabuseError::SmtpError(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
             StabuseError::EnvError(_) => actix_web::http::StatusCode::NOT_FOUND,
             StabuseError::Internal(_) => actix_web::http::StatusCode::NOT_FOUND,
+            StabuseError::Rpc(_) => actix_web::http::StatusCode::BAD_GATEWAY,
+            StabuseError::ServiceUnavailable(_) => actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
         }
     }
 }
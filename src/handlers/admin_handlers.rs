@@ -9,11 +9,15 @@ use crate::{
         verify_otp_and_login,
     },
     error::StabuseError,
+    queue::queue::VerificationQueue,
     types::types::{
-        AdminDetails, AdminInviteRequest, CreateAdminRequest, LoginCredentials, VerifyOtpRequest,
+        AdminDetails, AdminInviteRequest, CreateAdminRequest, DeadLetterQuery, LoginCredentials,
+        ReplayDeadLettersRequest, VerifyOtpRequest,
     },
 };
 
+const DEFAULT_DEAD_LETTER_LIMIT: usize = 100;
+
 pub async fn admin_login_handler(
     pool: web::Data<PgPool>,
     form: web::Json<LoginCredentials>,
@@ -127,3 +131,46 @@ pub async fn create_admin_with_invite_handler(
         }
     }
 }
+
+pub async fn list_dead_letters_handler(
+    queue: web::Data<dyn VerificationQueue>,
+    query: web::Query<DeadLetterQuery>,
+) -> Result<HttpResponse, StabuseError> {
+    let limit = query.limit.unwrap_or(DEFAULT_DEAD_LETTER_LIMIT);
+
+    match queue.list_dead_letters(limit).await {
+        Ok(dead_letters) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "dead_letters": dead_letters,
+        }))),
+        Err(err) => {
+            TracingError!(error = ?err, "Error listing dead-lettered verifications");
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to list dead letters: {}", err),
+            })))
+        }
+    }
+}
+
+pub async fn replay_dead_letters_handler(
+    queue: web::Data<dyn VerificationQueue>,
+    form: web::Json<ReplayDeadLettersRequest>,
+) -> Result<HttpResponse, StabuseError> {
+    let data = form.into_inner();
+
+    match queue.replay_dead_letters(data.message_id.as_deref()).await {
+        Ok(replayed) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Dead letters replayed",
+            "replayed": replayed,
+        }))),
+        Err(err) => {
+            TracingError!(error = ?err, "Error replaying dead-lettered verifications");
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to replay dead letters: {}", err),
+            })))
+        }
+    }
+}
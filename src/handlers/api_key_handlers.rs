@@ -0,0 +1,98 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use sqlx::PgPool;
+use tracing::error as TracingError;
+
+use crate::{
+    auth::api_key::{create_api_key, get_api_keys, revoke_api_key},
+    error::StabuseError,
+    types::types::{Claims, CreateApiKeyRequest, RevokeApiKeyRequest},
+};
+
+pub async fn get_api_keys_handler(req: HttpRequest, pool: web::Data<PgPool>) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match get_api_keys(&pool, id).await {
+        Ok(api_keys) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "api_keys": api_keys,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching API keys");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to fetch API keys: {}", e),
+            }))
+        }
+    }
+}
+
+/// The keys in the response are never shown again.
+pub async fn create_api_key_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<CreateApiKeyRequest>,
+) -> impl Responder {
+    let CreateApiKeyRequest { name, scopes, mode } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match create_api_key(&pool, id, &name, scopes, mode.unwrap_or_default()).await {
+        Ok(api_key) => HttpResponse::Created().json(serde_json::json!({
+            "status": "success",
+            "message": "API key created successfully",
+            "api_key": api_key,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error creating API key");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to create API key: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn revoke_api_key_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<RevokeApiKeyRequest>,
+) -> impl Responder {
+    let RevokeApiKeyRequest { key_id } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match revoke_api_key(&pool, id, key_id).await {
+        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "API key revoked successfully",
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error revoking API key");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to revoke API key: {}", e),
+            }))
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::error as TracingError;
+
+use crate::{
+    checkout::checkout::{
+        create_checkout_session, get_checkout_options, get_checkout_session, select_checkout_option,
+    },
+    error::StabuseError,
+    handlers::payment_handlers::api_key_mode,
+    types::types::{
+        CheckoutSelectionRequest, CheckoutSessionStatus, Claims, CreateCheckoutSessionRequest,
+    },
+};
+
+/// Sessions fix the amount the payer is charged, so only the merchant, or
+/// its server holding a secret key, may create them.
+pub async fn create_checkout_session_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Json<CreateCheckoutSessionRequest>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let mode = api_key_mode(&req).or(body.mode).unwrap_or_default();
+
+    match create_checkout_session(&pool, claims.sub, mode, &body).await {
+        Ok(session) => Ok(HttpResponse::Created().json(json!({
+            "status": "success",
+            "message": "Checkout session created successfully",
+            "checkout_session": session,
+        }))),
+        Err(StabuseError::InvalidData(msg)) => Ok(HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": msg,
+        }))),
+        Err(e) => {
+            TracingError!(error = ?e, "Error creating checkout session");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to create checkout session: {}", e),
+            })))
+        }
+    }
+}
+
+/// The session as the payer sees it, with the options still open to them.
+pub async fn get_checkout_session_handler(
+    pool: web::Data<PgPool>,
+    session_id: web::Path<String>,
+) -> Result<HttpResponse, StabuseError> {
+    let session = match get_checkout_session(&pool, &session_id).await {
+        Ok(session) => session,
+        Err(StabuseError::InvalidData(msg)) => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "status": "error",
+                "message": msg,
+            })))
+        }
+        Err(e) => return Err(e),
+    };
+
+    let options = if session.status == CheckoutSessionStatus::Open {
+        get_checkout_options(&pool, session.merchant_id, session.mode).await?
+    } else {
+        vec![]
+    };
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "checkout_session": session,
+        "options": options,
+    })))
+}
+
+pub async fn select_checkout_option_handler(
+    pool: web::Data<PgPool>,
+    session_id: web::Path<String>,
+    body: web::Json<CheckoutSelectionRequest>,
+) -> Result<HttpResponse, StabuseError> {
+    match select_checkout_option(&pool, &session_id, &body).await {
+        Ok((transaction, auth_details)) => Ok(HttpResponse::Ok().json(json!({
+            "status": "success",
+            "message": "Payment creation Successful",
+            "transaction": transaction,
+            "pending_payment_id": auth_details.pending_payment_id,
+            "token": auth_details.jwt_token,
+        }))),
+        Err(StabuseError::InvalidData(msg)) => Ok(HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": msg,
+        }))),
+        Err(e) => {
+            TracingError!(error = ?e, "Error creating checkout payment");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to create payment: {}", e),
+            })))
+        }
+    }
+}
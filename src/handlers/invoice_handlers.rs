@@ -0,0 +1,100 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::error as TracingError;
+
+use crate::{
+    error::StabuseError,
+    handlers::payment_handlers::api_key_mode,
+    invoice::invoice::{create_invoice, get_invoice, get_merchant_invoices},
+    types::types::{Claims, CreateInvoiceRequest, InvoiceQuery},
+};
+
+/// Invoices fix what payments against them must add up to, so like checkout
+/// sessions they are only created by the merchant or with a secret key.
+pub async fn create_invoice_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Json<CreateInvoiceRequest>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let mode = api_key_mode(&req).or(body.mode).unwrap_or_default();
+
+    match create_invoice(&pool, claims.sub, mode, &body).await {
+        Ok(invoice) => Ok(HttpResponse::Created().json(json!({
+            "status": "success",
+            "message": "Invoice created successfully",
+            "invoice": invoice,
+        }))),
+        Err(StabuseError::InvalidData(msg)) => Ok(HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": msg,
+        }))),
+        Err(e) => {
+            TracingError!(error = ?e, "Error creating invoice");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to create invoice: {}", e),
+            })))
+        }
+    }
+}
+
+pub async fn get_merchant_invoices_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<InvoiceQuery>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let mut query = query.into_inner();
+    if let Some(mode) = api_key_mode(&req) {
+        query.mode = Some(mode);
+    }
+
+    match get_merchant_invoices(&pool, claims.sub, &query).await {
+        Ok(page) => Ok(HttpResponse::Ok().json(json!({
+            "status": "success",
+            "invoices": page.invoices,
+            "next_cursor": page.next_cursor,
+        }))),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching merchant invoices");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to fetch invoices: {}", e),
+            })))
+        }
+    }
+}
+
+pub async fn get_merchant_invoice_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    invoice_id: web::Path<String>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    match get_invoice(&pool, claims.sub, &invoice_id, api_key_mode(&req)).await? {
+        Some(invoice) => Ok(HttpResponse::Ok().json(json!({
+            "status": "success",
+            "invoice": invoice,
+        }))),
+        None => Ok(HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Invoice not found",
+        }))),
+    }
+}
@@ -7,12 +7,16 @@ use crate::{
     error::StabuseError,
     merchant::merchant::{
         add_merchant_supported_network, add_new_merchant_network_asset, create_merchant_account,
-        merchant_login, remove_merchant_network_asset, update_merchant_network_address,
+        get_merchant_webhook_secrets, merchant_login, remove_merchant_network_asset,
+        rotate_merchant_webhook_secret, update_merchant_network_address,
+        update_merchant_payment_expiry, update_merchant_payment_tolerance,
     },
     types::types::{
         Claims, CreateMerchantRequest, LoginCredentials, MerchantAddressRequest,
-        MerchantAssetRequest, MerchantNetworkRequest,
+        MerchantAssetRequest, MerchantNetworkRequest, PaymentExpiryRequest, PaymentTolerance,
+        RotateWebhookSecretRequest, VerifyWebhookSignatureRequest,
     },
+    utils::signature::{verify_webhook_signature, DEFAULT_SIGNATURE_TOLERANCE_SECS},
 };
 
 pub async fn create_merchant_account_handler(
@@ -166,6 +170,179 @@ pub async fn update_merchant_network_address_handler(
     }
 }
 
+pub async fn update_payment_expiry_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<PaymentExpiryRequest>,
+) -> impl Responder {
+    let PaymentExpiryRequest {
+        payment_expiry_minutes,
+    } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match update_merchant_payment_expiry(&pool, id, payment_expiry_minutes).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Payment expiry updated successfully",
+            "payment_expiry_minutes": payment_expiry_minutes,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error updating payment expiry");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to update payment expiry: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn update_payment_tolerance_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<PaymentTolerance>,
+) -> impl Responder {
+    let tolerance = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match update_merchant_payment_tolerance(&pool, id, &tolerance).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Payment tolerance updated successfully",
+            "tolerance": tolerance,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error updating payment tolerance");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to update payment tolerance: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn get_webhook_secret_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match get_merchant_webhook_secrets(&pool, id).await {
+        Ok(secrets) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "webhook_secret": secrets.webhook_secret,
+            "previous_webhook_secret_expires_at": secrets.previous_webhook_secret_expires_at,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching webhook secret");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to fetch webhook secret: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn rotate_webhook_secret_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<RotateWebhookSecretRequest>,
+) -> impl Responder {
+    let RotateWebhookSecretRequest { overlap_minutes } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match rotate_merchant_webhook_secret(&pool, id, overlap_minutes).await {
+        Ok(secrets) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Webhook secret rotated successfully",
+            "webhook_secret": secrets.webhook_secret,
+            "previous_webhook_secret_expires_at": secrets.previous_webhook_secret_expires_at,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error rotating webhook secret");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to rotate webhook secret: {}", e),
+            }))
+        }
+    }
+}
+
+/// Checks a webhook body and `Stabuse-Signature` header against the
+/// merchant's current secrets, so SDK authors can test their verifiers.
+pub async fn verify_webhook_signature_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<VerifyWebhookSignatureRequest>,
+) -> Result<HttpResponse, StabuseError> {
+    let data = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let secrets = get_merchant_webhook_secrets(&pool, claims.sub).await?;
+    let now = chrono::Utc::now().timestamp();
+    let mut result = Ok(());
+    for secret in secrets.signing_secrets() {
+        result = verify_webhook_signature(
+            secret,
+            &data.signature,
+            &data.payload,
+            now,
+            DEFAULT_SIGNATURE_TOLERANCE_SECS,
+        );
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    match result {
+        Ok(()) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Signature is valid",
+            "valid": true,
+        }))),
+        Err(e) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": e.to_string(),
+            "valid": false,
+        }))),
+    }
+}
+
 pub async fn merchant_login_handler(
     pool: web::Data<PgPool>,
     credentials: web::Json<LoginCredentials>,
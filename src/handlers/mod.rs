@@ -1,7 +1,11 @@
 pub mod admin_handlers;
+pub mod api_key_handlers;
+pub mod checkout_handlers;
+pub mod invoice_handlers;
 pub mod merchant_handlers;
 pub mod network_handler;
 pub mod payment_handlers;
+pub mod webhook_handlers;
 
 use crate::db::db_init::init_db;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
@@ -6,9 +6,13 @@ use tracing::error as TracingError;
 use crate::{
     network::network::{
         add_asset_to_network, add_network, get_all_networks, get_network,
-        get_network_supported_assets,
+        get_network_supported_assets, update_asset_status, update_confirmation_policy,
+        update_rpc_endpoints,
+    },
+    types::types::{
+        AddAssetRequest, AdminClaims, Network, UpdateAssetStatusRequest,
+        UpdateConfirmationPolicyRequest, UpdateRpcEndpointsRequest,
     },
-    types::types::{AddAssetRequest, AdminClaims, Network},
 };
 
 pub async fn handle_add_network(
@@ -72,6 +76,84 @@ pub async fn handle_add_asset(
     }
 }
 
+pub async fn handle_update_asset_status(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Json<UpdateAssetStatusRequest>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<AdminClaims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let username = &claims.username;
+    let data = body.into_inner();
+
+    match update_asset_status(&pool, username, data.chain_id, &data.ticker, data.enabled).await {
+        Ok(_) => HttpResponse::Ok().body("Asset status updated successfully"),
+        Err(err) => {
+            TracingError!(error = ?err, "Error updating asset status");
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating asset status: {}", err))
+        }
+    }
+}
+
+pub async fn handle_update_confirmation_policy(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Json<UpdateConfirmationPolicyRequest>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<AdminClaims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let username = &claims.username;
+    let data = body.into_inner();
+
+    match update_confirmation_policy(&pool, username, data.chain_id, data.confirmation_policy).await
+    {
+        Ok(_) => HttpResponse::Ok().body("Confirmation policy updated successfully"),
+        Err(err) => {
+            TracingError!(error = ?err, "Error updating confirmation policy");
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating confirmation policy: {}", err))
+        }
+    }
+}
+
+pub async fn handle_update_rpc_endpoints(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Json<UpdateRpcEndpointsRequest>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<AdminClaims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let username = &claims.username;
+    let data = body.into_inner();
+
+    match update_rpc_endpoints(
+        &pool,
+        username,
+        data.chain_id,
+        data.rpc_endpoints,
+        data.rpc_quorum.unwrap_or(1),
+    )
+    .await
+    {
+        Ok(_) => HttpResponse::Ok().body("RPC endpoints updated successfully"),
+        Err(err) => {
+            TracingError!(error = ?err, "Error updating RPC endpoints");
+            HttpResponse::InternalServerError()
+                .json(format!("Error updating RPC endpoints: {}", err))
+        }
+    }
+}
+
 pub async fn handle_get_network(
     _req: HttpRequest,
     pool: web::Data<PgPool>,
@@ -1,78 +1,116 @@
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use futures::StreamExt;
 use serde_json::json;
 use sqlx::PgPool;
+use std::time::Duration;
 use tracing::error as TracingError;
 
 use crate::{
-    core::{evm::evm::create_payment_request, sol::sol::create_payment_transaction},
+    auth::jwt::pending_payment_claims,
+    core::{adapter::adapter::chain_adapter, rpc::rpc::with_failover},
     db::migrations::payments::select_queries::GET_PAYMENT_EXISTENCE_BY_HASH,
     error::StabuseError,
-    mq::mq::publish_message,
+    invoice::invoice::check_invoice_payable,
+    network::network::get_network,
+    payment::{
+        payment::{get_merchant_payment, get_merchant_payment_by_tx_hash, get_merchant_payments},
+        status::{
+            get_pending_payment_state, subscribe_status_updates, watch_pending_payment_state,
+        },
+    },
+    queue::queue::VerificationQueue,
     types::types::{
-        CreatePaymentRequest, PaymentClaims, TransactionVerificationMessage, ValidatePaymentRequest,
+        AccountMode, ApiKeyIdentity, Claims, CreatePaymentRequest, Payment, PaymentClaims,
+        PaymentHistoryQuery, TransactionVerificationMessage, ValidatePaymentRequest,
     },
 };
 
+const STATUS_STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
 pub async fn create_payment_request_handler(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     body: web::Json<CreatePaymentRequest>,
 ) -> Result<HttpResponse, StabuseError> {
     let data = body.into_inner();
-    if data.network.to_lowercase().contains("sol") {
-        match create_payment_transaction(
+    let identity = req
+        .extensions()
+        .get::<ApiKeyIdentity>()
+        .expect("API key identity must be present in request")
+        .clone();
+    let merchant_id = identity.merchant_id;
+    tracing::info!(
+        "Creating {} mode payment for merchant {} with {:?} API key {}",
+        identity.mode.as_str(),
+        merchant_id,
+        identity.kind,
+        identity.key_id
+    );
+    let network = get_network(&pool, data.chain_id).await?;
+    if AccountMode::for_network(&network) != identity.mode {
+        return Err(StabuseError::InvalidData(format!(
+            "{} mode API keys cannot create payments on {}",
+            identity.mode.as_str(),
+            network.name
+        )));
+    }
+    if let Some(invoice_id) = &data.invoice_id {
+        check_invoice_payable(
             &pool,
-            &data.rpc_url,
-            &data.user_address,
-            data.merchant_id,
-            &data.asset,
-            data.payment_amount,
+            merchant_id,
+            identity.mode,
+            invoice_id,
+            Some(&data.asset),
+            &data.payment_amount,
         )
-        .await
-        {
-            Ok((tx, token)) => Ok(HttpResponse::Ok().json(serde_json::json!({
-                "status": "success",
-                "message": "Payment creation Successful",
-                "transaction": tx,
-                "token": token.jwt_token,
-                "webhook_url": token.webhook_url
-            }))),
-            Err(e) => {
-                TracingError!(error = ?e, "Payment creation error");
-                Ok(HttpResponse::Unauthorized().json(json!({
-                    "error": "Invalid credentials"
-                })))
-            }
+        .await?;
+    }
+    let adapter = chain_adapter(network.chain_family);
+    let (pool, network_ref, data_ref) = (pool.get_ref(), &network, &data);
+
+    match with_failover(&network, |rpc_url| async move {
+        adapter
+            .create_payment(pool, network_ref, &rpc_url, merchant_id, data_ref)
+            .await
+    })
+    .await
+    {
+        Ok((tx, token)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Payment creation Successful",
+            "transaction": tx,
+            "token": token.jwt_token,
+        }))),
+        Err(StabuseError::InvalidData(msg)) => Ok(HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": msg,
+        }))),
+        Err(
+            e @ (StabuseError::InvalidAssetFormat(_) | StabuseError::AssetNotSupportedonNetwork(_)),
+        ) => Ok(HttpResponse::BadRequest().json(json!({
+            "status": "error",
+            "message": e.to_string(),
+        }))),
+        Err(e @ StabuseError::Rpc(_)) => {
+            TracingError!(error = ?e, "Payment creation error");
+            Ok(HttpResponse::BadGateway().json(json!({
+                "status": "error",
+                "message": format!("Failed to create payment: {}", e),
+            })))
         }
-    } else {
-        match create_payment_request(
-            &pool,
-            data.merchant_id,
-            data.payment_amount,
-            &data.user_address,
-            &data.rpc_url,
-            &data.asset,
-        )
-        .await
-        {
-            Ok((tx, token)) => Ok(HttpResponse::Ok().json(serde_json::json!({
-                "status": "success",
-                "message": "Payment creation Successful",
-                "transaction": tx,
-                "token": token.jwt_token,
-                "webhook_url": token.webhook_url
-            }))),
-            Err(e) => {
-                TracingError!(error = ?e, "Payment creation error");
-                Ok(HttpResponse::Unauthorized().json(json!({
-                    "error": "Invalid credentials"
-                })))
-            }
+        Err(e) => {
+            TracingError!(error = ?e, "Payment creation error");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to create payment: {}", e),
+            })))
         }
     }
 }
 
 pub async fn validate_payment_handler(
     req: HttpRequest,
+    queue: web::Data<dyn VerificationQueue>,
     body: web::Json<ValidatePaymentRequest>,
 ) -> Result<HttpResponse, StabuseError> {
     let data = body.into_inner();
@@ -85,20 +123,19 @@ pub async fn validate_payment_handler(
     let message = TransactionVerificationMessage {
         pending_payment_id: claims.pending_payment_id,
         tx_hash: data.tx_hash,
-        rpc_url: data.rpc_url,
+        chain_id: claims.chain_id,
         network: claims.network,
     };
 
-    let rabbitmq_url =
-        std::env::var("RABBITMQ_URL").expect("RABBITMQ_URL must be set in environment variables");
-    let queue_name =
-        std::env::var("QUEUE_NAME").expect("QUEUE_NAME must be set in environment variables");
-
-    match publish_message(&rabbitmq_url, &queue_name, message).await {
+    match queue.publish(&message).await {
         Ok(_) => Ok(HttpResponse::Ok().json(serde_json::json!({
             "status": "success",
             "message": "Payment validation request sent successfully"
         }))),
+        Err(e @ StabuseError::ServiceUnavailable(_)) => {
+            tracing::error!(error = ?e, "Verification queue unavailable for payment validation");
+            Err(e)
+        }
         Err(e) => {
             tracing::error!(error = ?e, "Failed to publish payment validation message");
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -124,3 +161,151 @@ pub async fn confirm_payment_transaction(
         Ok(HttpResponse::Ok().json(json!({ "status": "not_found" })))
     }
 }
+
+/// Current state of the payment the checkout page holds a token for.
+pub async fn payment_status_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<PaymentClaims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let state =
+        get_pending_payment_state(&pool, claims.pending_payment_id, claims.chain_id).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "payment": state,
+    })))
+}
+
+/// Server-Sent Events stream of the payment's state: a `status` event with
+/// the current state, then one per change until the payment is settled or
+/// expired. `EventSource` cannot send headers, so the payment token may also
+/// be given as `?token=`.
+pub async fn payment_status_stream_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = pending_payment_claims(&req).await?;
+
+    let updates = subscribe_status_updates();
+    let state =
+        get_pending_payment_state(&pool, claims.pending_payment_id, claims.chain_id).await?;
+
+    let events = watch_pending_payment_state(
+        pool.get_ref().clone(),
+        updates,
+        state,
+        claims.chain_id,
+        STATUS_STREAM_KEEP_ALIVE,
+    )
+    .map(|state| {
+        let frame = match state {
+            Some(state) => format!(
+                "event: status\ndata: {}\n\n",
+                serde_json::to_string(&state)?
+            ),
+            None => ": keep-alive\n\n".to_string(),
+        };
+        Ok::<_, StabuseError>(web::Bytes::from(frame))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events))
+}
+
+pub async fn get_merchant_payments_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<PaymentHistoryQuery>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let mut query = query.into_inner();
+    if let Some(mode) = api_key_mode(&req) {
+        query.mode = Some(mode);
+    }
+
+    match get_merchant_payments(&pool, claims.sub, &query).await {
+        Ok(page) => Ok(HttpResponse::Ok().json(json!({
+            "status": "success",
+            "payments": page.payments,
+            "next_cursor": page.next_cursor,
+            "total": page.total,
+        }))),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching merchant payments");
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to fetch payments: {}", e),
+            })))
+        }
+    }
+}
+
+pub async fn get_merchant_payment_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    payment_id: web::Path<i32>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let payment = get_merchant_payment(
+        &pool,
+        claims.sub,
+        payment_id.into_inner(),
+        api_key_mode(&req),
+    )
+    .await?;
+    Ok(payment_response(payment))
+}
+
+pub async fn get_merchant_payment_by_tx_hash_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    tx_hash: web::Path<String>,
+) -> Result<HttpResponse, StabuseError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+
+    let payment =
+        get_merchant_payment_by_tx_hash(&pool, claims.sub, &tx_hash, api_key_mode(&req)).await?;
+    Ok(payment_response(payment))
+}
+
+/// Requests made with a secret key only see the key's mode.
+pub fn api_key_mode(req: &HttpRequest) -> Option<AccountMode> {
+    req.extensions()
+        .get::<ApiKeyIdentity>()
+        .map(|identity| identity.mode)
+}
+
+fn payment_response(payment: Option<Payment>) -> HttpResponse {
+    match payment {
+        Some(payment) => HttpResponse::Ok().json(json!({
+            "status": "success",
+            "payment": payment,
+        })),
+        None => HttpResponse::NotFound().json(json!({
+            "status": "error",
+            "message": "Payment not found",
+        })),
+    }
+}
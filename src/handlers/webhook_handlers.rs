@@ -0,0 +1,244 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use sqlx::PgPool;
+use tracing::error as TracingError;
+
+use crate::{
+    error::StabuseError,
+    types::types::{
+        Claims, RemoveWebhookEndpointRequest, UpdateWebhookEndpointRequest, WebhookDeliveryQuery,
+        WebhookDeliveryRequest, WebhookEndpointRequest,
+    },
+    webhook::webhook::{
+        add_webhook_endpoint, get_webhook_deliveries, get_webhook_delivery, get_webhook_endpoints,
+        redeliver_webhook, remove_webhook_endpoint, update_webhook_endpoint,
+    },
+};
+
+pub async fn get_webhook_endpoints_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match get_webhook_endpoints(&pool, id).await {
+        Ok(endpoints) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "webhook_endpoints": endpoints,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching webhook endpoints");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to fetch webhook endpoints: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn add_webhook_endpoint_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<WebhookEndpointRequest>,
+) -> impl Responder {
+    let WebhookEndpointRequest {
+        url,
+        event_types,
+        mode,
+    } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match add_webhook_endpoint(
+        &pool,
+        id,
+        &url,
+        event_types.unwrap_or_default(),
+        mode.unwrap_or_default(),
+    )
+    .await
+    {
+        Ok(endpoint) => HttpResponse::Created().json(serde_json::json!({
+            "status": "success",
+            "message": "Webhook endpoint added successfully",
+            "webhook_endpoint": endpoint,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error adding webhook endpoint");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to add webhook endpoint: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn update_webhook_endpoint_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<UpdateWebhookEndpointRequest>,
+) -> impl Responder {
+    let update = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match update_webhook_endpoint(&pool, id, &update).await {
+        Ok(endpoint) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Webhook endpoint updated successfully",
+            "webhook_endpoint": endpoint,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error updating webhook endpoint");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to update webhook endpoint: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn remove_webhook_endpoint_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<RemoveWebhookEndpointRequest>,
+) -> impl Responder {
+    let RemoveWebhookEndpointRequest { id: endpoint_id } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match remove_webhook_endpoint(&pool, id, endpoint_id).await {
+        Ok(endpoint_id) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Webhook endpoint removed successfully",
+            "webhook_endpoint_id": endpoint_id,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error removing webhook endpoint");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to remove webhook endpoint: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn get_webhook_deliveries_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<WebhookDeliveryQuery>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match get_webhook_deliveries(&pool, id, &query).await {
+        Ok(deliveries) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "webhook_deliveries": deliveries,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching webhook deliveries");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to fetch webhook deliveries: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn get_webhook_delivery_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<WebhookDeliveryRequest>,
+) -> impl Responder {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match get_webhook_delivery(&pool, id, query.delivery_id).await {
+        Ok((delivery, attempts)) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "webhook_delivery": delivery,
+            "attempts": attempts,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error fetching webhook delivery");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to fetch webhook delivery: {}", e),
+            }))
+        }
+    }
+}
+
+pub async fn redeliver_webhook_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    form: web::Json<WebhookDeliveryRequest>,
+) -> impl Responder {
+    let WebhookDeliveryRequest { delivery_id } = form.into_inner();
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .expect("Claims must be present in request")
+        .clone();
+    let id = claims.sub;
+
+    match redeliver_webhook(&pool, id, delivery_id).await {
+        Ok(delivery_id) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "Webhook queued for redelivery",
+            "webhook_delivery_id": delivery_id,
+        })),
+        Err(StabuseError::InvalidData(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg,
+        })),
+        Err(e) => {
+            TracingError!(error = ?e, "Error redelivering webhook");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to redeliver webhook: {}", e),
+            }))
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use bigdecimal::{BigDecimal, One, RoundingMode, Zero};
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::{
+    db::migrations::invoices::{
+        inserts_and_updates::{ADD_INVOICE, ADD_INVOICE_LINE_ITEM},
+        select_queries::{GET_INVOICE, GET_INVOICES_FOR_MERCHANT},
+    },
+    error::StabuseError,
+    types::types::{
+        AccountMode, CreateInvoiceRequest, Invoice, InvoicePage, InvoiceQuery, InvoiceStatus,
+    },
+    utils::secret::generate_secret,
+};
+
+const INVOICE_PREFIX: &str = "inv_";
+const DEFAULT_INVOICE_PAGE_SIZE: i64 = 50;
+const MAX_INVOICE_PAGE_SIZE: i64 = 200;
+const MAX_LINE_ITEMS: usize = 100;
+const MAX_LINE_ITEM_DESCRIPTION_LENGTH: usize = 255;
+const MAX_EXTERNAL_ORDER_ID_LENGTH: usize = 255;
+/// Tax worked out from a rate is rounded to cents.
+const TAX_SCALE: i64 = 2;
+/// Payments are taken at par with the invoice currency, so invoices are
+/// limited to the currency the supported stablecoins are pegged to until
+/// there is a conversion rate to go by.
+const INVOICE_CURRENCIES: [&str; 1] = ["USD"];
+
+struct LineItem<'a> {
+    description: &'a str,
+    quantity: BigDecimal,
+    unit_amount: &'a BigDecimal,
+    amount: BigDecimal,
+}
+
+pub async fn create_invoice(
+    pool: &PgPool,
+    merchant_id: i32,
+    mode: AccountMode,
+    request: &CreateInvoiceRequest,
+) -> Result<Invoice, StabuseError> {
+    let currency = request.currency.trim().to_uppercase();
+    if !INVOICE_CURRENCIES.contains(&currency.as_str()) {
+        return Err(StabuseError::InvalidData(format!(
+            "Currency must be one of {}",
+            INVOICE_CURRENCIES.join(", ")
+        )));
+    }
+
+    let external_order_id = request.external_order_id.as_deref().map(str::trim);
+    if let Some(order_id) = external_order_id {
+        if order_id.is_empty() || order_id.len() > MAX_EXTERNAL_ORDER_ID_LENGTH {
+            return Err(StabuseError::InvalidData(format!(
+                "External order id must be between 1 and {} characters",
+                MAX_EXTERNAL_ORDER_ID_LENGTH
+            )));
+        }
+    }
+
+    if request.line_items.is_empty() || request.line_items.len() > MAX_LINE_ITEMS {
+        return Err(StabuseError::InvalidData(format!(
+            "An invoice needs between 1 and {} line items",
+            MAX_LINE_ITEMS
+        )));
+    }
+
+    let mut line_items = Vec::with_capacity(request.line_items.len());
+    let mut subtotal = BigDecimal::zero();
+    for item in &request.line_items {
+        let description = item.description.trim();
+        if description.is_empty() || description.len() > MAX_LINE_ITEM_DESCRIPTION_LENGTH {
+            return Err(StabuseError::InvalidData(format!(
+                "Line item descriptions must be between 1 and {} characters",
+                MAX_LINE_ITEM_DESCRIPTION_LENGTH
+            )));
+        }
+
+        let quantity = item.quantity.clone().unwrap_or_else(BigDecimal::one);
+        if quantity <= BigDecimal::zero() || item.unit_amount < BigDecimal::zero() {
+            return Err(StabuseError::InvalidData(format!(
+                "Line item \"{}\" needs a positive quantity and a non-negative unit amount",
+                description
+            )));
+        }
+
+        let amount = &quantity * &item.unit_amount;
+        subtotal += &amount;
+        line_items.push(LineItem {
+            description,
+            quantity,
+            unit_amount: &item.unit_amount,
+            amount,
+        });
+    }
+
+    let tax_amount = match (&request.tax_rate, &request.tax_amount) {
+        (Some(_), Some(_)) => {
+            return Err(StabuseError::InvalidData(
+                "Give either a tax rate or a tax amount, not both".to_string(),
+            ))
+        }
+        (Some(rate), None) => {
+            if *rate < BigDecimal::zero() || *rate > BigDecimal::from(100) {
+                return Err(StabuseError::InvalidData(
+                    "Tax rate must be a percentage between 0 and 100".to_string(),
+                ));
+            }
+            (&subtotal * rate / BigDecimal::from(100))
+                .with_scale_round(TAX_SCALE, RoundingMode::HalfUp)
+        }
+        (None, Some(amount)) => {
+            if *amount < BigDecimal::zero() {
+                return Err(StabuseError::InvalidData(
+                    "Tax amount cannot be negative".to_string(),
+                ));
+            }
+            amount.clone()
+        }
+        (None, None) => BigDecimal::zero(),
+    };
+
+    let total = &subtotal + &tax_amount;
+    if total <= BigDecimal::zero() {
+        return Err(StabuseError::InvalidData(
+            "Invoice total must be greater than zero".to_string(),
+        ));
+    }
+
+    let metadata = request.metadata.clone().unwrap_or_else(|| json!({}));
+    if !metadata.is_object() {
+        return Err(StabuseError::InvalidData(
+            "Invoice metadata must be a JSON object".to_string(),
+        ));
+    }
+
+    let description = request
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+    let invoice_id = format!("{}{}", INVOICE_PREFIX, generate_secret());
+
+    let mut tx = pool.begin().await?;
+
+    let id: Option<i32> = sqlx::query_scalar(ADD_INVOICE)
+        .bind(&invoice_id)
+        .bind(merchant_id)
+        .bind(mode)
+        .bind(external_order_id)
+        .bind(&currency)
+        .bind(description)
+        .bind(&subtotal)
+        .bind(&request.tax_rate)
+        .bind(&tax_amount)
+        .bind(&total)
+        .bind(&metadata)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(id) = id else {
+        return Err(StabuseError::InvalidData(format!(
+            "An invoice for order {} already exists",
+            external_order_id.unwrap_or_default()
+        )));
+    };
+
+    for (position, item) in line_items.iter().enumerate() {
+        sqlx::query(ADD_INVOICE_LINE_ITEM)
+            .bind(id)
+            .bind(position as i32)
+            .bind(item.description)
+            .bind(&item.quantity)
+            .bind(item.unit_amount)
+            .bind(&item.amount)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    get_invoice(pool, merchant_id, &invoice_id, Some(mode))
+        .await?
+        .ok_or_else(|| StabuseError::Internal(format!("Invoice {} was not saved", invoice_id)))
+}
+
+/// Looks an invoice up in either mode, or only in `mode` when given.
+pub async fn get_invoice(
+    pool: &PgPool,
+    merchant_id: i32,
+    invoice_id: &str,
+    mode: Option<AccountMode>,
+) -> Result<Option<Invoice>, StabuseError> {
+    let invoice = sqlx::query_as::<_, Invoice>(GET_INVOICE)
+        .bind(invoice_id)
+        .bind(merchant_id)
+        .bind(mode)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(invoice)
+}
+
+/// One page of the merchant's invoices, newest first, keyed on the invoice
+/// id like the payment history.
+pub async fn get_merchant_invoices(
+    pool: &PgPool,
+    merchant_id: i32,
+    query: &InvoiceQuery,
+) -> Result<InvoicePage, StabuseError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_INVOICE_PAGE_SIZE)
+        .clamp(1, MAX_INVOICE_PAGE_SIZE);
+
+    // One extra row tells whether another page follows.
+    let mut invoices = sqlx::query_as::<_, Invoice>(GET_INVOICES_FOR_MERCHANT)
+        .bind(merchant_id)
+        .bind(query.cursor)
+        .bind(query.status)
+        .bind(&query.external_order_id)
+        .bind(query.mode.unwrap_or_default())
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let next_cursor = if invoices.len() as i64 > limit {
+        invoices.truncate(limit as usize);
+        invoices.last().map(|invoice| invoice.id)
+    } else {
+        None
+    };
+
+    Ok(InvoicePage {
+        invoices,
+        next_cursor,
+    })
+}
+
+/// The fiat currency a stablecoin is pegged to, if it is one we know.
+fn pegged_currency(asset: &str) -> Option<&'static str> {
+    match asset.to_uppercase().as_str() {
+        "USDC" | "USDT" | "BUSD" | "DAI" => Some("USD"),
+        _ => None,
+    }
+}
+
+/// Checks that a payment of `amount` in `mode` may be made against the
+/// invoice. The asset is taken at par with the invoice currency, so it must
+/// be pegged to that currency and the amount must be the invoice total
+/// exactly. `asset` is `None` while the payer has yet to choose one, as for
+/// a new checkout session.
+pub async fn check_invoice_payable(
+    pool: &PgPool,
+    merchant_id: i32,
+    mode: AccountMode,
+    invoice_id: &str,
+    asset: Option<&str>,
+    amount: &BigDecimal,
+) -> Result<(), StabuseError> {
+    let invoice = get_invoice(pool, merchant_id, invoice_id, Some(mode))
+        .await?
+        .ok_or_else(|| StabuseError::InvalidData(format!("Invoice {} not found", invoice_id)))?;
+
+    if invoice.status != InvoiceStatus::Open {
+        return Err(StabuseError::InvalidData(format!(
+            "Invoice {} is already paid",
+            invoice_id
+        )));
+    }
+
+    if let Some(asset) = asset {
+        if pegged_currency(asset) != Some(invoice.currency.as_str()) {
+            return Err(StabuseError::InvalidData(format!(
+                "{} is not pegged to {}, the currency of invoice {}",
+                asset, invoice.currency, invoice_id
+            )));
+        }
+    }
+
+    if *amount != invoice.total {
+        return Err(StabuseError::InvalidData(format!(
+            "Amount must be the invoice total of {} {}",
+            invoice.total.normalized(),
+            invoice.currency
+        )));
+    }
+
+    Ok(())
+}
@@ -1,22 +1,29 @@
 mod admin;
 mod auth;
+mod checkout;
 mod core;
 mod db;
 mod error;
 mod handlers;
+mod invoice;
 mod merchant;
 mod mq;
 mod network;
+mod payment;
+mod queue;
 mod routes;
 mod types;
 mod utils;
+mod watcher;
+mod webhook;
 
 use actix_web::{web, App, HttpServer};
 use actix_web_prom::PrometheusMetricsBuilder;
 use db::db_init::connect_db;
 use dotenv::dotenv;
 use env_logger::Env;
-use mq::mq::start_consumer;
+use payment::status::start_payment_status_listener;
+use queue::queue::verification_queue_from_env;
 use routes::routes::{
     configure_admin_routes, configure_merchant_api_routes, configure_payment_routes,
     configure_public_routes,
@@ -24,12 +31,15 @@ use routes::routes::{
 use std::collections::HashMap;
 use tokio::spawn;
 use tracing::info;
+use watcher::{
+    confirmations::start_confirmation_tracker, expiry::start_expiry_sweeper,
+    watcher::start_watchers,
+};
+use webhook::delivery::start_webhook_delivery_worker;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
-    let rabbitmq_url = std::env::var("RABBITMQ_URL").expect("RABBITMQ_URL not set");
-    let queue_name = std::env::var("QUEUE_NAME").expect("QUEUE_NAME not set");
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
 
     let mut labels = HashMap::new();
@@ -46,17 +56,22 @@ async fn main() -> std::io::Result<()> {
 
     let pool = connect_db().await.expect("error conneting to db");
 
+    let queue = verification_queue_from_env(&pool).expect("error configuring verification queue");
+
+    let consumer_queue = queue.clone();
     let consumer_pool = pool.clone();
-    spawn(async move {
-        if let Err(err) = start_consumer(&rabbitmq_url, &queue_name, &consumer_pool).await {
-            tracing::error!("Error running RabbitMQ consumer: {:?}", err);
-        }
-    });
+    spawn(async move { consumer_queue.run_consumer(consumer_pool).await });
+    spawn(start_watchers(pool.clone(), queue.clone()));
+    spawn(start_confirmation_tracker(pool.clone()));
+    spawn(start_expiry_sweeper(pool.clone()));
+    spawn(start_webhook_delivery_worker(pool.clone()));
+    spawn(start_payment_status_listener(pool.clone()));
 
     HttpServer::new(move || {
         App::new()
             .wrap(prometheus.clone())
             .app_data(web::Data::new(pool.clone())) //uses Arc
+            .app_data(web::Data::from(queue.clone()))
             .configure(configure_public_routes)
             .configure(configure_merchant_api_routes)
             .configure(configure_admin_routes)
@@ -1,18 +1,23 @@
 use std::env;
 
 use crate::{
-    auth::jwt::generate_merchant_jwt,
+    auth::jwt::{generate_merchant_jwt, DEFAULT_PAYMENT_EXPIRY_MINUTES},
     db::migrations::merchants::{
         insert_and_update_merchants::{
             ADD_ASSET_MERCHANT, ADD_MERCHANT, ADD_MERCHANT_SUPPORTED_NETWORK,
-            REMOVE_ASSET_MERCHANT, UPDATE_NETWORK_ADDRESS_MERCHANT,
+            REMOVE_ASSET_MERCHANT, ROTATE_MERCHANT_WEBHOOK_SECRET, UPDATE_MERCHANT_PAYMENT_EXPIRY,
+            UPDATE_MERCHANT_PAYMENT_TOLERANCE, UPDATE_NETWORK_ADDRESS_MERCHANT,
+        },
+        select_queries::{
+            GET_MERCHANT_NETWORK_ADDRESS, GET_MERCHANT_PAYMENT_EXPIRY,
+            GET_MERCHANT_PAYMENT_TOLERANCE, GET_MERCHANT_WEBHOOK_SECRETS, LOGIN_ATTEMPT,
         },
-        select_queries::{GET_MERCHANT_NETWORK_ADDRESS, LOGIN_ATTEMPT},
     },
     error::StabuseError,
-    network::network::is_asset_supported_on_network,
-    types::types::{LoginResponse, MerchantCredentials},
+    network::network::{get_network, is_asset_supported_on_network},
+    types::types::{LoginResponse, MerchantCredentials, PaymentTolerance, WebhookSecrets},
     utils::{
+        secret::generate_secret,
         utils::hash_password,
         validation::{
             address_validation::validate_address,
@@ -22,8 +27,10 @@ use crate::{
     },
 };
 use bcrypt::verify;
+use bigdecimal::{BigDecimal, One, Signed};
+use chrono::Duration;
 use serde_json::{json, Value};
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, Row, Transaction};
 
 pub async fn create_merchant_account(
     pool: &PgPool,
@@ -54,6 +61,7 @@ pub async fn create_merchant_account(
         .bind(email)
         .bind(password_hash)
         .bind(assets)
+        .bind(generate_secret())
         .fetch_one(pool)
         .await?;
 
@@ -147,7 +155,8 @@ pub async fn add_merchant_supported_network(
     address: &str,
 ) -> Result<Value, StabuseError> {
     validate_supported_assets(pool, chain_id, supported_assets.clone()).await?;
-    validate_address(address)?;
+    let network = get_network(pool, chain_id).await?;
+    validate_address(address, network.chain_family)?;
 
     let networks = sqlx::query_scalar(ADD_MERCHANT_SUPPORTED_NETWORK)
         .bind(merchant_id)
@@ -167,7 +176,8 @@ pub async fn update_merchant_network_address(
     chain_id: i64,
     address: &str,
 ) -> Result<Value, StabuseError> {
-    validate_address(address)?;
+    let network = get_network(pool, chain_id).await?;
+    validate_address(address, network.chain_family)?;
     let updated_networks: Value = sqlx::query_scalar(UPDATE_NETWORK_ADDRESS_MERCHANT)
         .bind(chain_id)
         .bind(address)
@@ -195,3 +205,128 @@ pub async fn get_merchant_network_address(
 
     Ok(address)
 }
+
+/// Longest a merchant may keep a payment open: one week.
+const MAX_PAYMENT_EXPIRY_MINUTES: i32 = 7 * 24 * 60;
+
+pub async fn update_merchant_payment_expiry(
+    pool: &PgPool,
+    merchant_id: i32,
+    payment_expiry_minutes: Option<i32>,
+) -> Result<i32, StabuseError> {
+    if let Some(minutes) = payment_expiry_minutes {
+        if !(1..=MAX_PAYMENT_EXPIRY_MINUTES).contains(&minutes) {
+            return Err(StabuseError::InvalidData(format!(
+                "Payment expiry must be between 1 and {} minutes",
+                MAX_PAYMENT_EXPIRY_MINUTES
+            )));
+        }
+    }
+
+    let id = sqlx::query_scalar(UPDATE_MERCHANT_PAYMENT_EXPIRY)
+        .bind(merchant_id)
+        .bind(payment_expiry_minutes)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(id)
+}
+
+pub async fn get_merchant_payment_expiry(
+    pool: &PgPool,
+    merchant_id: i32,
+) -> Result<Duration, StabuseError> {
+    let minutes: Option<i32> = sqlx::query_scalar(GET_MERCHANT_PAYMENT_EXPIRY)
+        .bind(merchant_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Duration::minutes(
+        minutes.map_or(DEFAULT_PAYMENT_EXPIRY_MINUTES, i64::from),
+    ))
+}
+
+pub async fn update_merchant_payment_tolerance(
+    pool: &PgPool,
+    merchant_id: i32,
+    tolerance: &PaymentTolerance,
+) -> Result<i32, StabuseError> {
+    if tolerance.underpayment_tolerance.is_negative()
+        || tolerance.underpayment_tolerance >= BigDecimal::one()
+    {
+        return Err(StabuseError::InvalidData(
+            "Underpayment tolerance must be at least 0 and below 1".to_string(),
+        ));
+    }
+    if tolerance.overpayment_tolerance.is_negative() {
+        return Err(StabuseError::InvalidData(
+            "Overpayment tolerance must not be negative".to_string(),
+        ));
+    }
+
+    let id = sqlx::query_scalar(UPDATE_MERCHANT_PAYMENT_TOLERANCE)
+        .bind(merchant_id)
+        .bind(&tolerance.underpayment_tolerance)
+        .bind(&tolerance.overpayment_tolerance)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(id)
+}
+
+/// Read inside the caller's transaction, next to the payment row it locks,
+/// so a payment is judged in one consistent snapshot.
+pub async fn get_merchant_payment_tolerance(
+    tx: &mut Transaction<'_, Postgres>,
+    merchant_id: i32,
+) -> Result<PaymentTolerance, StabuseError> {
+    let tolerance = sqlx::query_as::<_, PaymentTolerance>(GET_MERCHANT_PAYMENT_TOLERANCE)
+        .bind(merchant_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    Ok(tolerance)
+}
+
+/// How long a rotated-out webhook secret keeps signing by default: one day.
+const DEFAULT_WEBHOOK_SECRET_OVERLAP_MINUTES: i32 = 24 * 60;
+/// Longest a rotated-out webhook secret may keep signing: one week.
+const MAX_WEBHOOK_SECRET_OVERLAP_MINUTES: i32 = 7 * 24 * 60;
+
+pub async fn get_merchant_webhook_secrets(
+    pool: &PgPool,
+    merchant_id: i32,
+) -> Result<WebhookSecrets, StabuseError> {
+    let secrets = sqlx::query_as::<_, WebhookSecrets>(GET_MERCHANT_WEBHOOK_SECRETS)
+        .bind(merchant_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(secrets)
+}
+
+/// Replaces the merchant's webhook secret with a new one. The old secret
+/// keeps signing webhooks alongside it for `overlap_minutes`, so merchants
+/// can roll their verifiers over without rejecting deliveries.
+pub async fn rotate_merchant_webhook_secret(
+    pool: &PgPool,
+    merchant_id: i32,
+    overlap_minutes: Option<i32>,
+) -> Result<WebhookSecrets, StabuseError> {
+    let overlap_minutes = overlap_minutes.unwrap_or(DEFAULT_WEBHOOK_SECRET_OVERLAP_MINUTES);
+    if !(0..=MAX_WEBHOOK_SECRET_OVERLAP_MINUTES).contains(&overlap_minutes) {
+        return Err(StabuseError::InvalidData(format!(
+            "Webhook secret overlap must be between 0 and {} minutes",
+            MAX_WEBHOOK_SECRET_OVERLAP_MINUTES
+        )));
+    }
+
+    let secrets = sqlx::query_as::<_, WebhookSecrets>(ROTATE_MERCHANT_WEBHOOK_SECRET)
+        .bind(merchant_id)
+        .bind(generate_secret())
+        .bind(overlap_minutes)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(secrets)
+}
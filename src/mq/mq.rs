@@ -1,25 +1,118 @@
 use crate::{
-    core::{evm::evm::verify_signed_transaction, sol::sol::verify_sol_signed_transaction},
-    types::types::{TransactionVerificationMessage, WebhookPayload},
-    utils::utils::send_webhook_notification,
+    error::StabuseError,
+    queue::queue::{retry_delay, verify_message, VerificationQueue, MAX_VERIFICATION_ATTEMPTS},
+    types::types::{DeadLetter, TransactionVerificationMessage},
 };
-use chrono::Utc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use lapin::{
-    message::Delivery, options::*, types::FieldTable, BasicProperties, Connection,
-    ConnectionProperties, Consumer,
+    message::Delivery,
+    options::*,
+    publisher_confirm::PublisherConfirm,
+    types::{AMQPValue, FieldTable, ShortString},
+    BasicProperties, Channel, Connection, ConnectionProperties, Consumer, ExchangeKind,
 };
 use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::Mutex;
 use tracing::error as TracingError;
+use uuid::Uuid;
 
-pub async fn start_consumer(
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+/// How long a publish may wait for the broker to confirm it.
+const PUBLISH_CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+
+const ATTEMPTS_HEADER: &str = "x-attempts";
+const LAST_ERROR_HEADER: &str = "x-last-error";
+
+fn retry_exchange(queue_name: &str) -> String {
+    format!("{}.retry", queue_name)
+}
+
+fn retry_queue(queue_name: &str, level: u32) -> String {
+    format!("{}.retry.{}", queue_name, level)
+}
+
+fn dead_letter_queue(queue_name: &str) -> String {
+    format!("{}.dead", queue_name)
+}
+
+/// Declares the retry exchange, one delay queue per retry level and the
+/// dead-letter queue. Messages parked in a delay queue expire back onto the
+/// main queue once their level's TTL has passed.
+async fn declare_retry_topology(channel: &Channel, queue_name: &str) -> Result<(), StabuseError> {
+    let durable = QueueDeclareOptions {
+        durable: true,
+        ..QueueDeclareOptions::default()
+    };
+
+    channel
+        .exchange_declare(
+            &retry_exchange(queue_name),
+            ExchangeKind::Direct,
+            ExchangeDeclareOptions {
+                durable: true,
+                ..ExchangeDeclareOptions::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    for level in 0..MAX_VERIFICATION_ATTEMPTS - 1 {
+        let mut arguments = FieldTable::default();
+        arguments.insert(
+            "x-message-ttl".into(),
+            AMQPValue::LongUInt(retry_delay(level + 1).as_millis() as u32),
+        );
+        arguments.insert(
+            "x-dead-letter-exchange".into(),
+            AMQPValue::LongString("".into()),
+        );
+        arguments.insert(
+            "x-dead-letter-routing-key".into(),
+            AMQPValue::LongString(queue_name.into()),
+        );
+
+        let queue = retry_queue(queue_name, level);
+        channel.queue_declare(&queue, durable, arguments).await?;
+        channel
+            .queue_bind(
+                &queue,
+                &retry_exchange(queue_name),
+                &level.to_string(),
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+    }
+
+    channel
+        .queue_declare(
+            &dead_letter_queue(queue_name),
+            durable,
+            FieldTable::default(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn consume(
     rabbitmq_url: &str,
     queue_name: &str,
     pool: &PgPool,
-) -> Result<(), Box<dyn std::error::Error>> {
+    reconnect_delay: &mut Duration,
+) -> Result<(), StabuseError> {
     let connection = Connection::connect(rabbitmq_url, ConnectionProperties::default()).await?;
     let channel = connection.create_channel().await?;
 
+    // Retries and dead letters are published on this channel and must be
+    // confirmed before the delivery they replace is acked.
+    channel
+        .confirm_select(ConfirmSelectOptions::default())
+        .await?;
     channel
         .queue_declare(
             queue_name,
@@ -27,6 +120,7 @@ pub async fn start_consumer(
             FieldTable::default(),
         )
         .await?;
+    declare_retry_topology(&channel, queue_name).await?;
 
     tracing::info!("Listening on queue: {}", queue_name);
 
@@ -40,17 +134,20 @@ pub async fn start_consumer(
         .await?;
 
     let mut consumer = consumer;
+    *reconnect_delay = RECONNECT_BASE_DELAY;
 
     tracing::info!("Waiting for messsages...");
     while let Some(delivery) = consumer.next().await {
-        if let Ok(delivery) = delivery {
-            let db_pool = pool.clone();
-            tokio::spawn(async move {
-                if let Err(err) = handle_message(delivery, &db_pool).await {
-                    eprintln!("Error handling message: {:?}", err);
-                }
-            });
-        }
+        // A delivery error means the connection is gone; reconnect.
+        let delivery = delivery?;
+        let db_pool = pool.clone();
+        let channel = channel.clone();
+        let queue_name = queue_name.to_string();
+        tokio::spawn(async move {
+            if let Err(err) = handle_message(delivery, &channel, &queue_name, &db_pool).await {
+                eprintln!("Error handling message: {:?}", err);
+            }
+        });
     }
 
     Ok(())
@@ -58,96 +155,365 @@ pub async fn start_consumer(
 
 async fn handle_message(
     delivery: Delivery,
+    channel: &Channel,
+    queue_name: &str,
     pool: &PgPool,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), StabuseError> {
     let message = String::from_utf8(delivery.data.clone()).unwrap_or_default();
     tracing::info!("Received message: {}", message);
 
+    let attempts = header_u32(&delivery.properties, ATTEMPTS_HEADER).unwrap_or(0);
+
     let message: TransactionVerificationMessage = match serde_json::from_slice(&delivery.data) {
         Ok(msg) => msg,
         Err(e) => {
             TracingError!(error = ?e, "Failed to deserialize message");
-            eprintln!("Failed to deserialize message: {}", e);
-            delivery.nack(BasicNackOptions::default()).await?;
+            // Malformed messages never succeed, so they skip the retries.
+            let reason = format!("Failed to deserialize message: {}", e);
+            dead_letter(channel, queue_name, &delivery.data, attempts, &reason).await?;
+            delivery.ack(BasicAckOptions::default()).await?;
             return Ok(());
         }
     };
     tracing::info!("Received message: {:?}", message);
 
-    let verification_result = if message.network.to_lowercase().contains("sol") {
-        verify_sol_signed_transaction(
-            &pool,
-            message.pending_payment_id,
-            &message.rpc_url,
-            &message.tx_hash,
+    if let Err(e) = verify_message(pool, &message).await {
+        let attempts = attempts + 1;
+        TracingError!(error = ?e, attempt = attempts, "Payment verification failed");
+        if attempts >= MAX_VERIFICATION_ATTEMPTS {
+            dead_letter(
+                channel,
+                queue_name,
+                &delivery.data,
+                attempts,
+                &e.to_string(),
+            )
+            .await?;
+        } else {
+            schedule_retry(channel, queue_name, &delivery.data, attempts).await?;
+        }
+    }
+
+    // Acked only once the broker has confirmed any retry or dead letter, so
+    // a crash in between redelivers the message instead of losing it.
+    delivery.ack(BasicAckOptions::default()).await?;
+    Ok(())
+}
+
+/// Parks a failed message in the delay queue for its attempt, from where it
+/// returns to the main queue after `retry_delay(attempts)`.
+async fn schedule_retry(
+    channel: &Channel,
+    queue_name: &str,
+    payload: &[u8],
+    attempts: u32,
+) -> Result<(), StabuseError> {
+    let mut headers = FieldTable::default();
+    headers.insert(ATTEMPTS_HEADER.into(), AMQPValue::LongUInt(attempts));
+
+    let publish = channel
+        .basic_publish(
+            &retry_exchange(queue_name),
+            &(attempts - 1).to_string(),
+            BasicPublishOptions::default(),
+            payload,
+            BasicProperties::default()
+                .with_delivery_mode(2)
+                .with_headers(headers),
         )
-        .await
-    } else {
-        verify_signed_transaction(
-            &pool,
-            message.pending_payment_id,
-            &message.rpc_url,
-            &message.tx_hash,
+        .await?;
+    confirmed(publish).await?;
+
+    tracing::info!(
+        "Verification retry {} scheduled in {}ms",
+        attempts,
+        retry_delay(attempts).as_millis()
+    );
+    Ok(())
+}
+
+async fn dead_letter(
+    channel: &Channel,
+    queue_name: &str,
+    payload: &[u8],
+    attempts: u32,
+    reason: &str,
+) -> Result<(), StabuseError> {
+    let mut headers = FieldTable::default();
+    headers.insert(ATTEMPTS_HEADER.into(), AMQPValue::LongUInt(attempts));
+    headers.insert(
+        LAST_ERROR_HEADER.into(),
+        AMQPValue::LongString(reason.into()),
+    );
+
+    let publish = channel
+        .basic_publish(
+            "",
+            &dead_letter_queue(queue_name),
+            BasicPublishOptions::default(),
+            payload,
+            BasicProperties::default()
+                .with_delivery_mode(2)
+                .with_message_id(Uuid::new_v4().to_string().into())
+                .with_timestamp(Utc::now().timestamp() as u64)
+                .with_headers(headers),
         )
-        .await
-    };
+        .await?;
+    confirmed(publish).await?;
 
-    match verification_result {
-        Ok((id, webhook_url)) => {
-            tracing::info!("Transaction verified successfully!");
-            delivery.ack(BasicAckOptions::default()).await?;
-            tracing::info!("Payment creation Successful with payment id: {}", id);
+    tracing::warn!(
+        "Verification dead-lettered after {} attempts: {}",
+        attempts,
+        reason
+    );
+    Ok(())
+}
 
-            let payload = WebhookPayload {
-                payment_id: id,
-                status: "completed".to_string(),
-                tx_hash: message.tx_hash.clone(),
-                timestamp: Utc::now().to_rfc3339(),
-            };
+/// Waits for the broker to confirm a publish made on a channel in confirm
+/// mode, bounded by `PUBLISH_CONFIRM_TIMEOUT`.
+async fn confirmed(publish: PublisherConfirm) -> Result<(), StabuseError> {
+    match tokio::time::timeout(PUBLISH_CONFIRM_TIMEOUT, publish).await {
+        Ok(Ok(confirmation)) if confirmation.is_ack() => Ok(()),
+        Ok(Ok(_)) => Err(StabuseError::ServiceUnavailable(
+            "Message broker rejected the message".to_string(),
+        )),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(StabuseError::ServiceUnavailable(
+            "Message broker did not confirm the message in time".to_string(),
+        )),
+    }
+}
 
-            let payload_json = serde_json::to_string(&payload)?;
+fn header_u32(properties: &BasicProperties, name: &str) -> Option<u32> {
+    match properties.headers().as_ref()?.inner().get(name)? {
+        AMQPValue::LongUInt(value) => Some(*value),
+        AMQPValue::LongInt(value) => u32::try_from(*value).ok(),
+        AMQPValue::LongLongInt(value) => u32::try_from(*value).ok(),
+        _ => None,
+    }
+}
 
-            send_webhook_notification(&webhook_url, &payload_json).await?;
-        }
-        Err(e) => {
-            eprintln!("Verification failed: {}", e);
-            delivery.nack(BasicNackOptions::default()).await?;
-            TracingError!(error = ?e, "Payment creation error");
-        }
+fn header_string(properties: &BasicProperties, name: &str) -> Option<String> {
+    match properties.headers().as_ref()?.inner().get(name)? {
+        AMQPValue::LongString(value) => Some(value.to_string()),
+        AMQPValue::ShortString(value) => Some(value.to_string()),
+        _ => None,
     }
+}
 
-    delivery.ack(BasicAckOptions::default()).await?;
-    Ok(())
+fn to_dead_letter(delivery: &Delivery) -> DeadLetter {
+    let properties = &delivery.properties;
+    DeadLetter {
+        message_id: properties
+            .message_id()
+            .as_ref()
+            .map(ShortString::to_string)
+            .unwrap_or_default(),
+        attempts: header_u32(properties, ATTEMPTS_HEADER).unwrap_or(0),
+        last_error: header_string(properties, LAST_ERROR_HEADER),
+        dead_lettered_at: properties
+            .timestamp()
+            .and_then(|timestamp| DateTime::<Utc>::from_timestamp(timestamp as i64, 0)),
+        message: serde_json::from_slice(&delivery.data).ok(),
+    }
 }
 
-pub async fn publish_message(
+/// Messages are fetched unacknowledged and return to the queue when the
+/// channel closes.
+async fn list_dead_letters(
     rabbitmq_url: &str,
     queue_name: &str,
-    message: TransactionVerificationMessage,
-) -> Result<(), Box<dyn std::error::Error>> {
+    limit: usize,
+) -> Result<Vec<DeadLetter>, StabuseError> {
     let connection = Connection::connect(rabbitmq_url, ConnectionProperties::default()).await?;
     let channel = connection.create_channel().await?;
+    declare_retry_topology(&channel, queue_name).await?;
+
+    let mut dead_letters = vec![];
+    while dead_letters.len() < limit {
+        let Some(message) = channel
+            .basic_get(&dead_letter_queue(queue_name), BasicGetOptions::default())
+            .await?
+        else {
+            break;
+        };
+        dead_letters.push(to_dead_letter(&message.delivery));
+    }
 
+    channel.close(200, "Dead letters listed").await?;
+    connection.close(200, "Dead letters listed").await?;
+    Ok(dead_letters)
+}
+
+async fn replay_dead_letters(
+    rabbitmq_url: &str,
+    queue_name: &str,
+    message_id: Option<&str>,
+) -> Result<usize, StabuseError> {
+    let connection = Connection::connect(rabbitmq_url, ConnectionProperties::default()).await?;
+    let channel = connection.create_channel().await?;
     channel
-        .queue_declare(
-            queue_name,
-            QueueDeclareOptions::default(),
-            FieldTable::default(),
-        )
+        .confirm_select(ConfirmSelectOptions::default())
         .await?;
+    declare_retry_topology(&channel, queue_name).await?;
 
-    let payload = serde_json::to_vec(&message)?;
+    let mut replayed = 0;
+    while let Some(message) = channel
+        .basic_get(&dead_letter_queue(queue_name), BasicGetOptions::default())
+        .await?
+    {
+        let delivery = message.delivery;
+        let id = delivery
+            .properties
+            .message_id()
+            .as_ref()
+            .map(ShortString::as_str);
+        if message_id.is_some() && id != message_id {
+            // Left unacknowledged; it goes back to the queue with the channel.
+            continue;
+        }
 
-    channel
-        .basic_publish(
-            "",
+        let publish = channel
+            .basic_publish(
+                "",
+                queue_name,
+                BasicPublishOptions::default(),
+                &delivery.data,
+                BasicProperties::default(),
+            )
+            .await?;
+        confirmed(publish).await?;
+        delivery.ack(BasicAckOptions::default()).await?;
+        replayed += 1;
+
+        if message_id.is_some() {
+            break;
+        }
+    }
+
+    channel.close(200, "Dead letters replayed").await?;
+    connection.close(200, "Dead letters replayed").await?;
+    Ok(replayed)
+}
+
+/// The RabbitMQ backend. Verification messages are published over one
+/// long-lived channel with publisher confirms. The channel is opened on first
+/// use and reopened after the connection drops; while the broker is
+/// unreachable, publishing fails with `StabuseError::ServiceUnavailable` so
+/// callers can back off. Retries wait in per-attempt TTL queues and exhausted
+/// messages land in `{queue}.dead`.
+pub struct RabbitMqQueue {
+    rabbitmq_url: String,
+    queue_name: String,
+    channel: Mutex<Option<Channel>>,
+}
+
+impl RabbitMqQueue {
+    pub fn new(rabbitmq_url: String, queue_name: String) -> Self {
+        RabbitMqQueue {
+            rabbitmq_url,
             queue_name,
-            BasicPublishOptions::default(),
-            &payload,
-            BasicProperties::default(),
-        )
-        .await?;
+            channel: Mutex::new(None),
+        }
+    }
 
-    tracing::info!("Message sent: {:?}", message);
-    Ok(())
+    async fn connect(&self) -> Result<Channel, lapin::Error> {
+        let connection =
+            Connection::connect(&self.rabbitmq_url, ConnectionProperties::default()).await?;
+        let channel = connection.create_channel().await?;
+
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+        channel
+            .queue_declare(
+                &self.queue_name,
+                QueueDeclareOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        tracing::info!("Publisher connected to queue: {}", self.queue_name);
+        Ok(channel)
+    }
+}
+
+#[async_trait]
+impl VerificationQueue for RabbitMqQueue {
+    async fn publish(&self, message: &TransactionVerificationMessage) -> Result<(), StabuseError> {
+        let payload = serde_json::to_vec(message)?;
+
+        let mut guard = self.channel.lock().await;
+        let channel = match guard.as_ref() {
+            Some(channel) if channel.status().connected() => channel.clone(),
+            _ => {
+                let channel = self.connect().await.map_err(|e| {
+                    StabuseError::ServiceUnavailable(format!("Message broker unreachable: {}", e))
+                })?;
+                *guard = Some(channel.clone());
+                channel
+            }
+        };
+
+        let result = tokio::time::timeout(PUBLISH_CONFIRM_TIMEOUT, async {
+            channel
+                .basic_publish(
+                    "",
+                    &self.queue_name,
+                    BasicPublishOptions::default(),
+                    &payload,
+                    BasicProperties::default(),
+                )
+                .await?
+                .await
+        })
+        .await;
+
+        match result {
+            Ok(Ok(confirmation)) if confirmation.is_ack() => {
+                tracing::info!("Message sent: {:?}", message);
+                Ok(())
+            }
+            Ok(Ok(_)) => Err(StabuseError::ServiceUnavailable(
+                "Message broker rejected the message".to_string(),
+            )),
+            Ok(Err(e)) => {
+                // Reconnect on the next publish.
+                *guard = None;
+                Err(StabuseError::ServiceUnavailable(format!(
+                    "Failed to publish message: {}",
+                    e
+                )))
+            }
+            Err(_) => {
+                *guard = None;
+                Err(StabuseError::ServiceUnavailable(
+                    "Message broker did not confirm the message in time".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Reconnects with a growing delay whenever the broker goes away.
+    async fn run_consumer(&self, pool: PgPool) {
+        let mut delay = RECONNECT_BASE_DELAY;
+
+        loop {
+            match consume(&self.rabbitmq_url, &self.queue_name, &pool, &mut delay).await {
+                Ok(()) => tracing::warn!("RabbitMQ consumer stream ended, reconnecting"),
+                Err(err) => TracingError!(error = ?err, "Error running RabbitMQ consumer"),
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+        }
+    }
+
+    async fn list_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>, StabuseError> {
+        list_dead_letters(&self.rabbitmq_url, &self.queue_name, limit).await
+    }
+
+    async fn replay_dead_letters(&self, message_id: Option<&str>) -> Result<usize, StabuseError> {
+        replay_dead_letters(&self.rabbitmq_url, &self.queue_name, message_id).await
+    }
 }
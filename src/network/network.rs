@@ -1,25 +1,30 @@
 use std::collections::HashMap;
 
 use crate::{
+    core::{adapter::adapter::chain_adapter, rpc::rpc::with_failover},
     db::migrations::networks::{
-        insert_and_update_networks::{ADD_ASSET, ADD_NETWORK},
+        insert_and_update_networks::{
+            ADD_ASSET, ADD_NETWORK, SET_ASSET_ENABLED, UPDATE_CONFIRMATION_POLICY,
+            UPDATE_RPC_ENDPOINTS,
+        },
         select_queries::{
-            CHECK_NETWORK_SUPPORTED_ASSET, GET_ALL_NETWORKS, GET_NETWORK, GET_NETWORK_AND_ASSETS,
-            GET_NETWORK_ASSETS,
+            CHECK_NETWORK_SUPPORTED_ASSET, GET_ALL_NETWORKS, GET_ALL_NETWORK_ASSETS, GET_NETWORK,
+            GET_NETWORK_ASSET, GET_NETWORK_ASSETS,
         },
     },
     error::StabuseError,
     types::{
         self,
-        types::{Asset, NetworkDB, NetworkInfo},
+        types::{ChainFamily, ConfirmationPolicy, NetworkAsset, NetworkDB, RpcEndpoint},
     },
     utils::{
-        utils::{hashmap_to_json_value, transform_assets_to_uppercase},
-        validation::domain_validation::validate_assets,
+        utils::transform_assets_to_uppercase,
+        validation::domain_validation::{
+            validate_assets, validate_confirmation_policy, validate_rpc_endpoints,
+        },
     },
 };
-use serde_json::Value;
-use sqlx::{PgPool, Row};
+use sqlx::{postgres::PgRow, PgPool, Row};
 
 use types::types::Network;
 
@@ -31,65 +36,197 @@ pub async fn add_network(
     /* TODO
     - Limit to only admins
     */
-    validate_assets(&network.supported_assets)?;
+    validate_assets(&network.supported_assets, network.chain_family)?;
     let assets = transform_assets_to_uppercase(&network.supported_assets);
+    let confirmation_policy = network
+        .confirmation_policy
+        .unwrap_or_else(|| default_confirmation_policy(network.chain_family));
+    validate_confirmation_policy(&network.name, network.chain_family, &confirmation_policy)?;
+
+    let mut rpc_endpoints = network.rpc_endpoints.unwrap_or_default();
+    if !rpc_endpoints
+        .iter()
+        .any(|endpoint| endpoint.url == network.rpc)
+    {
+        rpc_endpoints.insert(
+            0,
+            RpcEndpoint {
+                url: network.rpc.clone(),
+                weight: 1,
+            },
+        );
+    }
+    let rpc_quorum = network.rpc_quorum.unwrap_or(1);
+    validate_rpc_endpoints(&rpc_endpoints, rpc_quorum)?;
+
+    let adapter = chain_adapter(network.chain_family);
+    let chain_identifier = adapter.chain_identifier(&network.rpc).await?;
+    if network.chain_family == ChainFamily::Evm && chain_identifier != network.chain_id.to_string()
+    {
+        return Err(StabuseError::InvalidData(format!(
+            "RPC endpoint reports chain ID {} but the network was registered as {}",
+            chain_identifier, network.chain_id
+        )));
+    }
+
+    let mut registered_assets = vec![];
+    for (ticker, address) in assets {
+        let (decimals, symbol) = adapter
+            .fetch_asset_metadata(&network.rpc, &ticker, &address)
+            .await?;
+        registered_assets.push((ticker, address, decimals, symbol));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query_scalar(ADD_NETWORK)
+        .bind(network.chain_id)
+        .bind(&network.name)
+        .bind(network.chain_family)
+        .bind(chain_identifier)
+        .bind(&network.rpc)
+        .bind(serde_json::to_value(&rpc_endpoints)?)
+        .bind(rpc_quorum as i32)
+        .bind(confirmation_policy.mode())
+        .bind(confirmation_policy.depth())
+        .bind(admin_username)
+        .bind(network.is_testnet.unwrap_or(false))
+        .fetch_one(&mut *tx)
+        .await?;
 
-    match hashmap_to_json_value(assets) {
-        Ok(json_assets) => {
-            let id = sqlx::query_scalar(ADD_NETWORK)
-                .bind(network.chain_id)
-                .bind(network.name)
-                .bind(network.rpc)
-                .bind(json_assets)
-                .bind(admin_username)
-                .fetch_one(pool)
-                .await?;
-            Ok(id)
-        }
-        Err(e) => return Err(e),
+    for (ticker, address, decimals, symbol) in registered_assets {
+        sqlx::query(ADD_ASSET)
+            .bind(network.chain_id)
+            .bind(ticker)
+            .bind(address)
+            .bind(decimals as i16)
+            .bind(symbol)
+            .bind(admin_username)
+            .execute(&mut *tx)
+            .await?;
     }
+
+    tx.commit().await?;
+
+    Ok(id)
 }
 
 pub async fn add_asset_to_network(
     pool: &PgPool,
     admin_username: &str,
-    chain_id: i32,
+    chain_id: i64,
     asset: HashMap<String, String>,
 ) -> Result<(), StabuseError> {
-    validate_assets(&asset)?;
+    let network = get_network(pool, chain_id).await?;
+    validate_assets(&asset, network.chain_family)?;
     let assets = transform_assets_to_uppercase(&asset);
 
-    for (key, value) in assets.clone() {
-        match hashmap_to_json_value(asset.clone()) {
-            Ok(_) => {
-                sqlx::query(ADD_ASSET)
-                    .bind(key)
-                    .bind(value)
-                    .bind(chain_id)
-                    .bind(admin_username)
-                    .execute(pool)
-                    .await?;
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
+    let adapter = chain_adapter(network.chain_family);
+
+    for (ticker, address) in assets {
+        let (ticker_ref, address_ref) = (&ticker, &address);
+        let (decimals, symbol) = with_failover(&network, |rpc_url| async move {
+            adapter
+                .fetch_asset_metadata(&rpc_url, ticker_ref, address_ref)
+                .await
+        })
+        .await?;
+
+        sqlx::query(ADD_ASSET)
+            .bind(chain_id)
+            .bind(ticker)
+            .bind(address)
+            .bind(decimals as i16)
+            .bind(symbol)
+            .bind(admin_username)
+            .execute(pool)
+            .await?;
     }
 
     Ok(())
 }
 
-pub async fn get_network_supported_assets(
+pub async fn update_asset_status(
     pool: &PgPool,
+    admin_username: &str,
     chain_id: i64,
-) -> Result<Asset, StabuseError> {
-    let result: (Value,) = sqlx::query_as(GET_NETWORK_ASSETS)
+    ticker: &str,
+    enabled: bool,
+) -> Result<i32, StabuseError> {
+    let id = sqlx::query_scalar(SET_ASSET_ENABLED)
         .bind(chain_id)
+        .bind(ticker.to_uppercase())
+        .bind(enabled)
+        .bind(admin_username)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            StabuseError::AssetNotSupportedonNetwork(format!(
+                "Asset {} not found on network {}",
+                ticker, chain_id
+            ))
+        })?;
+
+    Ok(id)
+}
+
+pub async fn update_confirmation_policy(
+    pool: &PgPool,
+    admin_username: &str,
+    chain_id: i64,
+    confirmation_policy: ConfirmationPolicy,
+) -> Result<i32, StabuseError> {
+    let network = get_network(pool, chain_id).await?;
+    validate_confirmation_policy(&network.name, network.chain_family, &confirmation_policy)?;
+
+    let id = sqlx::query_scalar(UPDATE_CONFIRMATION_POLICY)
+        .bind(chain_id)
+        .bind(confirmation_policy.mode())
+        .bind(confirmation_policy.depth())
+        .bind(admin_username)
         .fetch_one(pool)
         .await?;
 
-    let assets: Asset =
-        serde_json::from_value(result.0).map_err(|e| StabuseError::SerdeError(e.to_string()))?;
+    Ok(id)
+}
+
+pub async fn update_rpc_endpoints(
+    pool: &PgPool,
+    admin_username: &str,
+    chain_id: i64,
+    mut rpc_endpoints: Vec<RpcEndpoint>,
+    rpc_quorum: u32,
+) -> Result<i32, StabuseError> {
+    validate_rpc_endpoints(&rpc_endpoints, rpc_quorum)?;
+    rpc_endpoints.sort_by_key(|endpoint| std::cmp::Reverse(endpoint.weight));
+
+    let id = sqlx::query_scalar(UPDATE_RPC_ENDPOINTS)
+        .bind(chain_id)
+        .bind(&rpc_endpoints[0].url)
+        .bind(serde_json::to_value(&rpc_endpoints)?)
+        .bind(rpc_quorum as i32)
+        .bind(admin_username)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(id)
+}
+
+fn default_confirmation_policy(chain_family: ChainFamily) -> ConfirmationPolicy {
+    match chain_family {
+        ChainFamily::Evm => ConfirmationPolicy::Depth { blocks: 12 },
+        ChainFamily::Solana => ConfirmationPolicy::Finalized,
+    }
+}
+
+pub async fn get_network_supported_assets(
+    pool: &PgPool,
+    chain_id: i64,
+) -> Result<Vec<NetworkAsset>, StabuseError> {
+    let assets = sqlx::query_as::<_, NetworkAsset>(GET_NETWORK_ASSETS)
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await?;
 
     Ok(assets)
 }
@@ -99,76 +236,60 @@ pub async fn get_network(pool: &PgPool, chain_id: i64) -> Result<NetworkDB, Stab
         .bind(chain_id)
         .fetch_one(pool)
         .await?;
+    let supported_assets = get_network_supported_assets(pool, chain_id).await?;
 
-    let supported_assets: HashMap<String, String> =
-        match row.try_get::<Value, _>("supported_assets")? {
-            Value::Object(map) => map
-                .into_iter()
-                .map(|(k, v)| match v {
-                    Value::String(s) => Ok((k, s)),
-                    _ => Err("Expected string values for supported_assets".to_string()),
-                })
-                .collect::<Result<_, _>>()
-                .expect("Error"),
-            _ => {
-                return Err(StabuseError::SerdeError(
-                    "Invalid JSON format for supported_assets".into(),
-                ))
-            }
-        };
-
-    let network = NetworkDB {
-        id: row.try_get("id")?,
-        chain_id: row.try_get("chain_id")?,
-        name: row.try_get("name")?,
-        rpc_url: row.try_get("rpc")?,
-        supported_assets,
-        created_at: row.try_get("created_at").ok(),
-        updated_at: row.try_get("updated_at").ok(),
-    };
-
-    Ok(network)
+    network_from_row(&row, supported_assets)
 }
 
 pub async fn get_all_networks(pool: &PgPool) -> Result<Vec<NetworkDB>, StabuseError> {
     let rows = sqlx::query(GET_ALL_NETWORKS).fetch_all(pool).await?;
 
+    let mut assets_by_chain: HashMap<i64, Vec<NetworkAsset>> = HashMap::new();
+    for asset in sqlx::query_as::<_, NetworkAsset>(GET_ALL_NETWORK_ASSETS)
+        .fetch_all(pool)
+        .await?
+    {
+        assets_by_chain
+            .entry(asset.chain_id)
+            .or_default()
+            .push(asset);
+    }
+
     let mut networks = vec![];
 
     for row in rows.iter() {
-        let supported_assets: HashMap<String, String> =
-            match row.try_get::<Value, _>("supported_assets")? {
-                Value::Object(map) => map
-                    .into_iter()
-                    .map(|(k, v)| match v {
-                        Value::String(s) => Ok((k, s)),
-                        _ => Err("Expected string values for supported_assets".to_string()),
-                    })
-                    .collect::<Result<_, _>>()
-                    .expect("Error"),
-                _ => {
-                    return Err(StabuseError::SerdeError(
-                        "Invalid JSON format for supported_assets".into(),
-                    ))
-                }
-            };
-
-        let network = NetworkDB {
-            id: row.try_get("id")?,
-            chain_id: row.try_get("chain_id")?,
-            name: row.try_get("name")?,
-            rpc_url: row.try_get("rpc")?,
-            supported_assets,
-            created_at: row.try_get("created_at").ok(),
-            updated_at: row.try_get("updated_at").ok(),
-        };
-
-        networks.push(network);
+        let chain_id: i64 = row.try_get("chain_id")?;
+        let supported_assets = assets_by_chain.remove(&chain_id).unwrap_or_default();
+        networks.push(network_from_row(row, supported_assets)?);
     }
 
     Ok(networks)
 }
 
+fn network_from_row(
+    row: &PgRow,
+    supported_assets: Vec<NetworkAsset>,
+) -> Result<NetworkDB, StabuseError> {
+    Ok(NetworkDB {
+        id: row.try_get("id")?,
+        chain_id: row.try_get("chain_id")?,
+        name: row.try_get("name")?,
+        chain_family: row.try_get("chain_family")?,
+        chain_identifier: row.try_get("chain_identifier")?,
+        is_testnet: row.try_get("is_testnet")?,
+        rpc_url: row.try_get("rpc")?,
+        supported_assets,
+        confirmation_policy: ConfirmationPolicy::from_columns(
+            row.try_get("confirmation_mode")?,
+            row.try_get("confirmation_depth")?,
+        )?,
+        rpc_endpoints: serde_json::from_value(row.try_get("rpc_endpoints")?)?,
+        rpc_quorum: row.try_get::<i32, _>("rpc_quorum")? as u32,
+        created_at: row.try_get("created_at").ok(),
+        updated_at: row.try_get("updated_at").ok(),
+    })
+}
+
 pub async fn is_asset_supported_on_network(
     pool: &PgPool,
     chain_id: i64,
@@ -183,33 +304,31 @@ pub async fn is_asset_supported_on_network(
     Ok(exists.0)
 }
 
-pub async fn get_network_and_asset_address_with_chain_id(
+/// Looks up an enabled asset on a network; every amount conversion goes
+/// through the decimals stored here.
+pub async fn get_network_asset(
     pool: &PgPool,
-    asset: &str,
-    chain_id: u64,
-) -> Result<(String, String), StabuseError> {
-    let network_info = sqlx::query_as::<_, NetworkInfo>(GET_NETWORK_AND_ASSETS)
-        .bind(chain_id as i64)
+    chain_id: i64,
+    ticker: &str,
+) -> Result<NetworkAsset, StabuseError> {
+    let asset = sqlx::query_as::<_, NetworkAsset>(GET_NETWORK_ASSET)
+        .bind(chain_id)
+        .bind(ticker)
         .fetch_optional(pool)
-        .await
-        .map_err(|e| StabuseError::DatabaseError(e))?
-        .ok_or_else(|| {
-            StabuseError::AssetNotSupportedonNetwork(format!(
-                "Chain ID {} not found in networks",
-                chain_id
-            ))
-        })?;
-
-    let asset_address = network_info
-        .supported_assets
-        .get(asset)
-        .and_then(Value::as_str)
+        .await?
         .ok_or_else(|| {
             StabuseError::AssetNotSupportedonNetwork(format!(
                 "Asset {} not found on network {}",
-                asset, chain_id
+                ticker, chain_id
             ))
         })?;
 
-    Ok((network_info.name, asset_address.to_string()))
+    if !asset.enabled {
+        return Err(StabuseError::AssetNotSupportedonNetwork(format!(
+            "Asset {} is disabled on network {}",
+            ticker, chain_id
+        )));
+    }
+
+    Ok(asset)
 }
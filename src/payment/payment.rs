@@ -0,0 +1,807 @@
+use bigdecimal::{BigDecimal, One, Zero};
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    db::migrations::{
+        checkout::inserts_and_updates::COMPLETE_CHECKOUT_SESSION,
+        invoices::{inserts_and_updates::SET_INVOICE_PAID, select_queries::GET_INVOICE_REFERENCE},
+        payments::{
+            inserts_and_updates::{
+                ADD_PAYMENT, ADD_PAYMENT_TRANSFER, ADD_PENDING_PAYMENT_RECEIVED,
+                EXPIRE_PENDING_PAYMENT, EXPIRE_PENDING_PAYMENTS, REMOVE_PAYMENT_TRANSFER,
+                SET_PENDING_PAYMENT_CONFIRMED, SET_PENDING_PAYMENT_CONFIRMING,
+                SET_PENDING_PAYMENT_FAILED, SET_PENDING_PAYMENT_REORGED, SET_PENDING_PAYMENT_SEEN,
+                SET_PENDING_PAYMENT_UNDERPAID, UPDATE_PAYMENT_TRANSFER_INCLUSION,
+            },
+            select_queries::{
+                COUNT_PAYMENTS_FOR_MERCHANT, GET_PAYMENTS_FOR_MERCHANT, GET_PAYMENT_BY_ID,
+                GET_PAYMENT_BY_TX_HASH, GET_PAYMENT_FOR_PENDING_OR_TX, GET_PAYMENT_TRANSFERS,
+                GET_PAYMENT_TRANSFER_OWNER, LOCK_PENDING_PAYMENT,
+            },
+        },
+    },
+    error::StabuseError,
+    merchant::merchant::get_merchant_payment_tolerance,
+    payment::status::publish_payment_status,
+    types::types::{
+        AccountMode, InvoiceReference, Payment, PaymentHistoryPage, PaymentHistoryQuery,
+        PaymentStatus, PaymentTransfer, PendingPayment, TransactionInclusion, WebhookPayload,
+    },
+    webhook::webhook::enqueue_webhook_event,
+};
+
+const DEFAULT_PAYMENT_PAGE_SIZE: i64 = 50;
+const MAX_PAYMENT_PAGE_SIZE: i64 = 200;
+
+/// Counts a transfer found on-chain towards a pending payment. Payments
+/// accumulate transfers until the total reaches the requested amount, less
+/// the merchant's underpayment tolerance, at which point they are `seen` and
+/// tracked for confirmations; until then they stay `underpaid`.
+///
+/// Transfers arriving after the payment is `seen` still count: they are
+/// recorded without changing its status, and `complete_payment` settles it
+/// as `overpaid` if they take it past the tolerance.
+///
+/// Recording the same transfer twice, e.g. when a verification message is
+/// redelivered, is a no-op.
+pub async fn record_payment_transfer(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+    inclusion: &TransactionInclusion,
+    amount: &BigDecimal,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    // Serialises concurrent verifications of transfers for the same payment.
+    let locked = sqlx::query_as::<_, PendingPayment>(LOCK_PENDING_PAYMENT)
+        .bind(pending_payment.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let inserted: Option<i32> = sqlx::query_scalar(ADD_PAYMENT_TRANSFER)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .bind(amount)
+        .bind(inclusion.block_number as i64)
+        .bind(&inclusion.block_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if inserted.is_none() {
+        let owner: i32 = sqlx::query_scalar(GET_PAYMENT_TRANSFER_OWNER)
+            .bind(tx_hash)
+            .fetch_one(&mut *tx)
+            .await?;
+        if owner == pending_payment.id {
+            return Ok(());
+        }
+        return Err(StabuseError::InvalidData(format!(
+            "Transaction {} is already used for payment {}",
+            tx_hash, owner
+        )));
+    }
+
+    let amount_received: Option<BigDecimal> = sqlx::query_scalar(ADD_PENDING_PAYMENT_RECEIVED)
+        .bind(pending_payment.id)
+        .bind(amount)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let Some(amount_received) = amount_received else {
+        return Err(StabuseError::InvalidData(format!(
+            "Pending payment {} is not awaiting a transaction",
+            pending_payment.id
+        )));
+    };
+
+    if matches!(
+        locked.status,
+        PaymentStatus::Seen | PaymentStatus::Confirming
+    ) {
+        tx.commit().await?;
+        tracing::info!(
+            "Extra transfer {} recorded for pending payment {}, now {} received",
+            tx_hash,
+            pending_payment.id,
+            amount_received
+        );
+        return Ok(());
+    }
+
+    let tolerance = get_merchant_payment_tolerance(&mut tx, pending_payment.merchant_id).await?;
+    let minimum = &pending_payment.amount * (BigDecimal::one() - &tolerance.underpayment_tolerance);
+
+    let status = if amount_received < minimum {
+        sqlx::query_scalar::<_, i32>(SET_PENDING_PAYMENT_UNDERPAID)
+            .bind(pending_payment.id)
+            .bind(tx_hash)
+            .fetch_one(&mut *tx)
+            .await?;
+        PaymentStatus::Underpaid
+    } else {
+        sqlx::query_scalar::<_, i32>(SET_PENDING_PAYMENT_SEEN)
+            .bind(pending_payment.id)
+            .bind(tx_hash)
+            .bind(inclusion.block_number as i64)
+            .bind(&inclusion.block_hash)
+            .fetch_one(&mut *tx)
+            .await?;
+        PaymentStatus::Seen
+    };
+
+    notify_payment_event(
+        &mut tx,
+        pending_payment,
+        status,
+        Some(tx_hash.to_string()),
+        0,
+        &amount_received,
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Re-marks a payment as seen after its transaction moved to another block.
+pub async fn mark_payment_seen(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+    inclusion: &TransactionInclusion,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let updated: Option<i32> = sqlx::query_scalar(SET_PENDING_PAYMENT_SEEN)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .bind(inclusion.block_number as i64)
+        .bind(&inclusion.block_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if updated.is_none() {
+        return Err(StabuseError::InvalidData(format!(
+            "Pending payment {} is not awaiting a transaction",
+            pending_payment.id
+        )));
+    }
+
+    sqlx::query(UPDATE_PAYMENT_TRANSFER_INCLUSION)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .bind(inclusion.block_number as i64)
+        .bind(&inclusion.block_hash)
+        .execute(&mut *tx)
+        .await?;
+
+    notify_payment_event(
+        &mut tx,
+        pending_payment,
+        PaymentStatus::Seen,
+        Some(tx_hash.to_string()),
+        0,
+        &pending_payment.amount_received,
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn mark_payment_confirming(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    confirmations: u64,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query_scalar::<_, i32>(SET_PENDING_PAYMENT_CONFIRMING)
+        .bind(pending_payment.id)
+        .bind(confirmations as i64)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if pending_payment.status == PaymentStatus::Seen {
+        notify_payment_event(
+            &mut tx,
+            pending_payment,
+            PaymentStatus::Confirming,
+            pending_payment.tx_hash.clone(),
+            confirmations as i64,
+            &pending_payment.amount_received,
+            None,
+        )
+        .await?;
+    } else if updated.is_some() && pending_payment.confirmations != confirmations as i64 {
+        // Merchants only hear about the move to `confirming`, but checkout
+        // pages follow every new confirmation.
+        let invoice = invoice_reference(&mut tx, pending_payment).await?;
+        let update = payment_event_payload(
+            pending_payment,
+            PaymentStatus::Confirming,
+            pending_payment.tx_hash.clone(),
+            confirmations as i64,
+            &pending_payment.amount_received,
+            None,
+            invoice,
+        );
+        publish_payment_status(&mut tx, &update).await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Settles a payment once its transaction is deep enough. Payments that
+/// received more than the merchant's overpayment tolerance allows settle as
+/// `overpaid` instead of `confirmed`.
+///
+/// The pending payment row is locked for the whole settlement, and settling
+/// an already settled payment returns the existing payment id.
+pub async fn complete_payment(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    confirmations: u64,
+) -> Result<i32, StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let pending_payment = sqlx::query_as::<_, PendingPayment>(LOCK_PENDING_PAYMENT)
+        .bind(pending_payment.id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let pending_payment = &pending_payment;
+
+    let tx_hash = pending_payment.tx_hash.clone().ok_or_else(|| {
+        StabuseError::Internal(format!(
+            "Pending payment {} has no transaction",
+            pending_payment.id
+        ))
+    })?;
+
+    let tolerance = get_merchant_payment_tolerance(&mut tx, pending_payment.merchant_id).await?;
+    let maximum = &pending_payment.amount * (BigDecimal::one() + &tolerance.overpayment_tolerance);
+    let status = if pending_payment.amount_received > maximum {
+        PaymentStatus::Overpaid
+    } else {
+        PaymentStatus::Confirmed
+    };
+
+    if matches!(
+        pending_payment.status,
+        PaymentStatus::Confirmed | PaymentStatus::Overpaid
+    ) {
+        return existing_payment_id(&mut tx, pending_payment, &tx_hash).await;
+    }
+
+    let updated: Option<i32> = sqlx::query_scalar(SET_PENDING_PAYMENT_CONFIRMED)
+        .bind(pending_payment.id)
+        .bind(confirmations as i64)
+        .bind(status)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if updated.is_none() {
+        return Err(StabuseError::InvalidData(format!(
+            "Pending payment {} is not awaiting confirmation",
+            pending_payment.id
+        )));
+    }
+
+    let inserted: Option<i32> = sqlx::query_scalar(ADD_PAYMENT)
+        .bind(pending_payment.merchant_id)
+        .bind(&pending_payment.sender)
+        .bind(&pending_payment.amount)
+        .bind(&tx_hash)
+        .bind(&pending_payment.asset)
+        .bind(&pending_payment.network)
+        .bind(&pending_payment.amount_received)
+        .bind(pending_payment.id)
+        .bind(pending_payment.mode)
+        .bind(&pending_payment.invoice_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let id = match inserted {
+        Some(id) => id,
+        None => existing_payment_id(&mut tx, pending_payment, &tx_hash).await?,
+    };
+
+    sqlx::query(COMPLETE_CHECKOUT_SESSION)
+        .bind(pending_payment.id)
+        .execute(&mut *tx)
+        .await?;
+
+    if let Some(invoice_id) = &pending_payment.invoice_id {
+        sqlx::query(SET_INVOICE_PAID)
+            .bind(invoice_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    notify_payment_event(
+        &mut tx,
+        pending_payment,
+        status,
+        Some(tx_hash),
+        confirmations as i64,
+        &pending_payment.amount_received,
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!("Payment creation Successful with payment id: {}", id);
+
+    Ok(id)
+}
+
+/// Looks up the payment a pending payment already settled into, or reports
+/// the payment that already used its transaction.
+async fn existing_payment_id(
+    tx: &mut Transaction<'_, Postgres>,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+) -> Result<i32, StabuseError> {
+    let existing: Option<(i32, Option<i32>)> = sqlx::query_as(GET_PAYMENT_FOR_PENDING_OR_TX)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    match existing {
+        Some((id, Some(pending_payment_id))) if pending_payment_id == pending_payment.id => Ok(id),
+        Some((id, other)) => Err(StabuseError::InvalidData(format!(
+            "Transaction {} is already used for payment {}",
+            tx_hash,
+            other.unwrap_or(id)
+        ))),
+        None => Err(StabuseError::Internal(format!(
+            "Pending payment {} is settled but has no payment",
+            pending_payment.id
+        ))),
+    }
+}
+
+pub async fn mark_payment_reorged(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let updated: Option<i32> = sqlx::query_scalar(SET_PENDING_PAYMENT_REORGED)
+        .bind(pending_payment.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if updated.is_some() {
+        tracing::info!(
+            "Transaction for pending payment {} was reorged out of block {:?}",
+            pending_payment.id,
+            pending_payment.block_hash
+        );
+        notify_payment_event(
+            &mut tx,
+            pending_payment,
+            PaymentStatus::Reorged,
+            pending_payment.tx_hash.clone(),
+            0,
+            &pending_payment.amount_received,
+            None,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Every transfer counted towards a pending payment, oldest first.
+pub async fn get_payment_transfers(
+    pool: &PgPool,
+    pending_payment_id: i32,
+) -> Result<Vec<PaymentTransfer>, StabuseError> {
+    let transfers = sqlx::query_as::<_, PaymentTransfer>(GET_PAYMENT_TRANSFERS)
+        .bind(pending_payment_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(transfers)
+}
+
+/// Records the block an earlier transfer of a split payment was re-included
+/// in after a reorg. The payment's confirmations follow from the new block.
+pub async fn move_payment_transfer(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+    inclusion: &TransactionInclusion,
+) -> Result<(), StabuseError> {
+    sqlx::query(UPDATE_PAYMENT_TRANSFER_INCLUSION)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .bind(inclusion.block_number as i64)
+        .bind(&inclusion.block_hash)
+        .execute(pool)
+        .await?;
+
+    tracing::info!(
+        "Transfer {} for pending payment {} moved to block {}",
+        tx_hash,
+        pending_payment.id,
+        inclusion.block_number
+    );
+    Ok(())
+}
+
+/// Like [`mark_payment_reorged`], but for a transfer that is no longer on
+/// the chain at all, so it stops counting towards the payment. When it was
+/// an earlier transfer of a split payment, the payment is judged again on
+/// the transfers that remain.
+pub async fn mark_payment_dropped(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let updated: Option<i32> = sqlx::query_scalar(SET_PENDING_PAYMENT_REORGED)
+        .bind(pending_payment.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if updated.is_none() {
+        return Ok(());
+    }
+
+    let amount_received: BigDecimal = sqlx::query_scalar(REMOVE_PAYMENT_TRANSFER)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    notify_payment_event(
+        &mut tx,
+        pending_payment,
+        PaymentStatus::Reorged,
+        Some(tx_hash.to_string()),
+        0,
+        &amount_received,
+        None,
+    )
+    .await?;
+
+    if let (Some(latest_tx_hash), Some(block_number), Some(block_hash)) = (
+        pending_payment.tx_hash.as_deref(),
+        pending_payment.block_number,
+        pending_payment.block_hash.as_deref(),
+    ) {
+        if latest_tx_hash != tx_hash {
+            let tolerance =
+                get_merchant_payment_tolerance(&mut tx, pending_payment.merchant_id).await?;
+            let minimum =
+                &pending_payment.amount * (BigDecimal::one() - &tolerance.underpayment_tolerance);
+
+            let status = if amount_received < minimum {
+                sqlx::query_scalar::<_, i32>(SET_PENDING_PAYMENT_UNDERPAID)
+                    .bind(pending_payment.id)
+                    .bind(latest_tx_hash)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                PaymentStatus::Underpaid
+            } else {
+                sqlx::query_scalar::<_, i32>(SET_PENDING_PAYMENT_SEEN)
+                    .bind(pending_payment.id)
+                    .bind(latest_tx_hash)
+                    .bind(block_number)
+                    .bind(block_hash)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                PaymentStatus::Seen
+            };
+
+            notify_payment_event(
+                &mut tx,
+                pending_payment,
+                status,
+                Some(latest_tx_hash.to_string()),
+                0,
+                &amount_received,
+                None,
+            )
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+    tracing::info!(
+        "Transfer {} for pending payment {} was dropped from the chain",
+        tx_hash,
+        pending_payment.id
+    );
+
+    Ok(())
+}
+
+pub async fn mark_payment_failed(
+    pool: &PgPool,
+    pending_payment: &PendingPayment,
+    tx_hash: &str,
+    reason: &str,
+) -> Result<(), StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let updated: Option<i32> = sqlx::query_scalar(SET_PENDING_PAYMENT_FAILED)
+        .bind(pending_payment.id)
+        .bind(tx_hash)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if updated.is_some() {
+        notify_payment_event(
+            &mut tx,
+            pending_payment,
+            PaymentStatus::Failed,
+            Some(tx_hash.to_string()),
+            0,
+            &pending_payment.amount_received,
+            Some(reason.to_string()),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Moves every open payment past its `expires_at` to `expired` and notifies
+/// the merchant. Payments already paid in full are left to confirm. A
+/// reorged payment gets an hour past its `expires_at`, or past the reorg if
+/// that came later, for its transaction to be included again.
+pub async fn expire_pending_payments(pool: &PgPool) -> Result<usize, StabuseError> {
+    let mut tx = pool.begin().await?;
+
+    let expired = sqlx::query_as::<_, PendingPayment>(EXPIRE_PENDING_PAYMENTS)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    for pending_payment in &expired {
+        tracing::info!("Pending payment {} expired", pending_payment.id);
+        notify_payment_event(
+            &mut tx,
+            pending_payment,
+            PaymentStatus::Expired,
+            pending_payment.tx_hash.clone(),
+            0,
+            &pending_payment.amount_received,
+            None,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(expired.len())
+}
+
+/// Expires a pending payment that a newer one replaces, in the caller's
+/// transaction. Returns `false`, leaving it untouched, when funds for it are
+/// already on their way.
+pub async fn supersede_pending_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    pending_payment_id: i32,
+) -> Result<bool, StabuseError> {
+    let pending_payment = sqlx::query_as::<_, PendingPayment>(LOCK_PENDING_PAYMENT)
+        .bind(pending_payment_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    match pending_payment.status {
+        PaymentStatus::Expired => Ok(true),
+        PaymentStatus::Created | PaymentStatus::Failed => {
+            sqlx::query(EXPIRE_PENDING_PAYMENT)
+                .bind(pending_payment_id)
+                .execute(&mut **tx)
+                .await?;
+
+            tracing::info!("Pending payment {} superseded", pending_payment_id);
+            notify_payment_event(
+                tx,
+                &pending_payment,
+                PaymentStatus::Expired,
+                pending_payment.tx_hash.clone(),
+                0,
+                &pending_payment.amount_received,
+                None,
+            )
+            .await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Writes the webhook event for a status change to the outbox and announces
+/// it to open status streams, in the same transaction as the change itself.
+async fn notify_payment_event(
+    tx: &mut Transaction<'_, Postgres>,
+    pending_payment: &PendingPayment,
+    status: PaymentStatus,
+    tx_hash: Option<String>,
+    confirmations: i64,
+    amount_received: &BigDecimal,
+    failure_reason: Option<String>,
+) -> Result<(), StabuseError> {
+    let invoice = invoice_reference(tx, pending_payment).await?;
+    let payload = payment_event_payload(
+        pending_payment,
+        status,
+        tx_hash,
+        confirmations,
+        amount_received,
+        failure_reason,
+        invoice,
+    );
+
+    let payload_json = serde_json::to_string(&payload)?;
+    enqueue_webhook_event(
+        tx,
+        pending_payment.merchant_id,
+        pending_payment.mode,
+        &payload.event,
+        &payload_json,
+    )
+    .await?;
+
+    publish_payment_status(tx, &payload).await
+}
+
+fn payment_event_payload(
+    pending_payment: &PendingPayment,
+    status: PaymentStatus,
+    tx_hash: Option<String>,
+    confirmations: i64,
+    amount_received: &BigDecimal,
+    failure_reason: Option<String>,
+    invoice: Option<InvoiceReference>,
+) -> WebhookPayload {
+    // Only report a difference once something has actually been received.
+    let difference = amount_received - &pending_payment.amount;
+    let (shortfall, excess) = if amount_received.is_zero() || difference.is_zero() {
+        (None, None)
+    } else if difference < BigDecimal::zero() {
+        (Some(-difference), None)
+    } else {
+        (None, Some(difference))
+    };
+
+    WebhookPayload {
+        event: format!("payment.{}", status.as_str()),
+        payment_id: pending_payment.id,
+        mode: pending_payment.mode,
+        status,
+        tx_hash,
+        confirmations,
+        amount: pending_payment.amount.clone(),
+        amount_received: amount_received.clone(),
+        shortfall,
+        excess,
+        failure_reason,
+        invoice,
+        timestamp: Utc::now().to_rfc3339(),
+    }
+}
+
+/// The invoice a payment was made against, for its webhooks to echo.
+async fn invoice_reference(
+    tx: &mut Transaction<'_, Postgres>,
+    pending_payment: &PendingPayment,
+) -> Result<Option<InvoiceReference>, StabuseError> {
+    let Some(invoice_id) = &pending_payment.invoice_id else {
+        return Ok(None);
+    };
+
+    let invoice = sqlx::query_as::<_, InvoiceReference>(GET_INVOICE_REFERENCE)
+        .bind(invoice_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    Ok(invoice)
+}
+
+/// One page of the merchant's settled payments, newest first. Pages are
+/// keyed on the payment id, so payments settling while a merchant pages
+/// through the history neither repeat nor shift later pages.
+pub async fn get_merchant_payments(
+    pool: &PgPool,
+    merchant_id: i32,
+    query: &PaymentHistoryQuery,
+) -> Result<PaymentHistoryPage, StabuseError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAYMENT_PAGE_SIZE)
+        .clamp(1, MAX_PAYMENT_PAGE_SIZE);
+    let asset = query.asset.as_deref().map(str::to_uppercase);
+    let mode = query.mode.unwrap_or_default();
+
+    // One extra row tells whether another page follows.
+    let mut payments = sqlx::query_as::<_, Payment>(GET_PAYMENTS_FOR_MERCHANT)
+        .bind(merchant_id)
+        .bind(query.cursor)
+        .bind(&query.network)
+        .bind(&asset)
+        .bind(query.status)
+        .bind(&query.sender)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(limit + 1)
+        .bind(mode)
+        .bind(&query.invoice_id)
+        .bind(&query.external_order_id)
+        .fetch_all(pool)
+        .await?;
+
+    let next_cursor = if payments.len() as i64 > limit {
+        payments.truncate(limit as usize);
+        payments.last().map(|payment| payment.id)
+    } else {
+        None
+    };
+
+    let total: i64 = sqlx::query_scalar(COUNT_PAYMENTS_FOR_MERCHANT)
+        .bind(merchant_id)
+        .bind(&query.network)
+        .bind(&asset)
+        .bind(query.status)
+        .bind(&query.sender)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(mode)
+        .bind(&query.invoice_id)
+        .bind(&query.external_order_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(PaymentHistoryPage {
+        payments,
+        next_cursor,
+        total,
+    })
+}
+
+/// Looks a payment up in either mode, or only in `mode` when given.
+pub async fn get_merchant_payment(
+    pool: &PgPool,
+    merchant_id: i32,
+    payment_id: i32,
+    mode: Option<AccountMode>,
+) -> Result<Option<Payment>, StabuseError> {
+    let payment = sqlx::query_as::<_, Payment>(GET_PAYMENT_BY_ID)
+        .bind(payment_id)
+        .bind(merchant_id)
+        .bind(mode)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(payment)
+}
+
+pub async fn get_merchant_payment_by_tx_hash(
+    pool: &PgPool,
+    merchant_id: i32,
+    tx_hash: &str,
+    mode: Option<AccountMode>,
+) -> Result<Option<Payment>, StabuseError> {
+    let payment = sqlx::query_as::<_, Payment>(GET_PAYMENT_BY_TX_HASH)
+        .bind(tx_hash)
+        .bind(merchant_id)
+        .bind(mode)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(payment)
+}
@@ -0,0 +1,159 @@
+use futures::{stream, Stream};
+use sqlx::{postgres::PgListener, PgPool, Postgres, Transaction};
+use std::{sync::OnceLock, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::error as TracingError;
+
+use crate::{
+    db::migrations::payments::{
+        inserts_and_updates::NOTIFY_PAYMENT_STATUS, select_queries::GET_PENDING_PAYMENT,
+    },
+    error::StabuseError,
+    network::network::get_network,
+    types::types::{ConfirmationPolicy, PendingPayment, PendingPaymentState, WebhookPayload},
+};
+
+const PAYMENT_STATUS_CHANNEL: &str = "payment_status";
+const STATUS_BROADCAST_CAPACITY: usize = 1024;
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+fn status_updates() -> &'static broadcast::Sender<WebhookPayload> {
+    static UPDATES: OnceLock<broadcast::Sender<WebhookPayload>> = OnceLock::new();
+    UPDATES.get_or_init(|| broadcast::channel(STATUS_BROADCAST_CAPACITY).0)
+}
+
+/// Starts receiving status changes. Subscribe before reading the state a
+/// watch starts from, so a change landing in between is not missed.
+pub fn subscribe_status_updates() -> broadcast::Receiver<WebhookPayload> {
+    status_updates().subscribe()
+}
+
+/// Announces a status change to every server instance. Postgres only
+/// delivers the notification once `tx` commits, so listeners never see a
+/// state that was rolled back.
+pub async fn publish_payment_status(
+    tx: &mut Transaction<'_, Postgres>,
+    update: &WebhookPayload,
+) -> Result<(), StabuseError> {
+    sqlx::query(NOTIFY_PAYMENT_STATUS)
+        .bind(PAYMENT_STATUS_CHANNEL)
+        .bind(serde_json::to_string(update)?)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Listens for payment status notifications and fans them out to the
+/// status streams open on this instance, reconnecting if the listener's
+/// connection drops.
+pub async fn start_payment_status_listener(pool: PgPool) {
+    loop {
+        if let Err(err) = listen_for_payment_status(&pool).await {
+            TracingError!(error = ?err, "Payment status listener error");
+        }
+
+        tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+    }
+}
+
+async fn listen_for_payment_status(pool: &PgPool) -> Result<(), StabuseError> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(PAYMENT_STATUS_CHANNEL).await?;
+
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<WebhookPayload>(notification.payload()) {
+            // Sending only fails when no stream is open, which is fine.
+            Ok(update) => {
+                let _ = status_updates().send(update);
+            }
+            Err(err) => {
+                TracingError!(error = ?err, "Invalid payment status notification");
+            }
+        }
+    }
+}
+
+pub async fn get_pending_payment_state(
+    pool: &PgPool,
+    pending_payment_id: i32,
+    chain_id: i64,
+) -> Result<PendingPaymentState, StabuseError> {
+    let pending_payment = sqlx::query_as::<_, PendingPayment>(GET_PENDING_PAYMENT)
+        .bind(pending_payment_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| StabuseError::InvalidData("Pending payment not found".to_string()))?;
+
+    let network = get_network(pool, chain_id).await?;
+    let required_confirmations = match network.confirmation_policy {
+        ConfirmationPolicy::Depth { blocks } => Some(blocks),
+        ConfirmationPolicy::Finalized | ConfirmationPolicy::Confirmed => None,
+    };
+
+    Ok(PendingPaymentState {
+        id: pending_payment.id,
+        mode: pending_payment.mode,
+        status: pending_payment.status,
+        asset: pending_payment.asset,
+        network: pending_payment.network,
+        amount: pending_payment.amount,
+        amount_received: pending_payment.amount_received,
+        tx_hash: pending_payment.tx_hash,
+        confirmations: pending_payment.confirmations,
+        required_confirmations,
+        failure_reason: pending_payment.failure_reason,
+        expires_at: pending_payment.expires_at,
+    })
+}
+
+/// Yields the payment's current state, then its state after every change
+/// received on `updates`, ending once the payment can no longer change.
+/// `None` stands for a quiet period of `keep_alive`, so callers can keep the
+/// connection open.
+pub fn watch_pending_payment_state(
+    pool: PgPool,
+    updates: broadcast::Receiver<WebhookPayload>,
+    state: PendingPaymentState,
+    chain_id: i64,
+    keep_alive: Duration,
+) -> impl Stream<Item = Option<PendingPaymentState>> {
+    stream::unfold(
+        (pool, updates, Some(state), true),
+        move |(pool, mut updates, state, initial)| async move {
+            let mut state = state?;
+            if initial {
+                let next = (!state.status.is_final()).then(|| state.clone());
+                return Some((Some(state), (pool, updates, next, false)));
+            }
+
+            let deadline = tokio::time::Instant::now() + keep_alive;
+            loop {
+                let received = match tokio::time::timeout_at(deadline, updates.recv()).await {
+                    Ok(received) => received,
+                    Err(_) => return Some((None, (pool, updates, Some(state), false))),
+                };
+
+                match received {
+                    Ok(update) if update.payment_id == state.id => state.apply(&update),
+                    Ok(_) => continue,
+                    // Missed updates are recovered by re-reading the payment.
+                    Err(RecvError::Lagged(_)) => {
+                        match get_pending_payment_state(&pool, state.id, chain_id).await {
+                            Ok(current) => state = current,
+                            Err(err) => {
+                                TracingError!(error = ?err, "Error reloading payment status");
+                                continue;
+                            }
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+
+                let next = (!state.status.is_final()).then(|| state.clone());
+                return Some((Some(state), (pool, updates, next, false)));
+            }
+        },
+    )
+}
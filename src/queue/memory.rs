@@ -0,0 +1,158 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    Mutex as AsyncMutex,
+};
+use tracing::error as TracingError;
+use uuid::Uuid;
+
+use crate::{
+    error::StabuseError,
+    queue::queue::{retry_delay, verify_message, VerificationQueue, MAX_VERIFICATION_ATTEMPTS},
+    types::types::{DeadLetter, TransactionVerificationMessage},
+};
+
+struct Job {
+    message_id: String,
+    message: TransactionVerificationMessage,
+    attempts: u32,
+}
+
+/// Keeps verification jobs in process memory, for local development and
+/// tests that should not need a broker. Queued jobs and dead letters are
+/// lost when the server stops.
+pub struct InMemoryQueue {
+    sender: UnboundedSender<Job>,
+    receiver: AsyncMutex<Option<UnboundedReceiver<Job>>>,
+    dead_letters: Arc<Mutex<Vec<DeadLetter>>>,
+}
+
+impl InMemoryQueue {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+        InMemoryQueue {
+            sender,
+            receiver: AsyncMutex::new(Some(receiver)),
+            dead_letters: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    fn send(&self, job: Job) -> Result<(), StabuseError> {
+        self.sender.send(job).map_err(|_| {
+            StabuseError::ServiceUnavailable("In-memory verification queue is closed".to_string())
+        })
+    }
+}
+
+#[async_trait]
+impl VerificationQueue for InMemoryQueue {
+    async fn publish(&self, message: &TransactionVerificationMessage) -> Result<(), StabuseError> {
+        self.send(Job {
+            message_id: Uuid::new_v4().to_string(),
+            message: message.clone(),
+            attempts: 0,
+        })?;
+
+        tracing::info!("Message sent: {:?}", message);
+        Ok(())
+    }
+
+    async fn run_consumer(&self, pool: PgPool) {
+        let Some(mut receiver) = self.receiver.lock().await.take() else {
+            TracingError!("In-memory verification consumer is already running");
+            return;
+        };
+
+        tracing::info!("Waiting for messsages...");
+        while let Some(job) = receiver.recv().await {
+            let (pool, sender, dead_letters) =
+                (pool.clone(), self.sender.clone(), self.dead_letters.clone());
+            tokio::spawn(async move {
+                handle_job(job, &pool, sender, &dead_letters).await;
+            });
+        }
+    }
+
+    async fn list_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>, StabuseError> {
+        let dead_letters = self
+            .dead_letters
+            .lock()
+            .map_err(|e| StabuseError::Internal(e.to_string()))?;
+
+        Ok(dead_letters.iter().take(limit).cloned().collect())
+    }
+
+    async fn replay_dead_letters(&self, message_id: Option<&str>) -> Result<usize, StabuseError> {
+        let replay: Vec<DeadLetter> = {
+            let mut dead_letters = self
+                .dead_letters
+                .lock()
+                .map_err(|e| StabuseError::Internal(e.to_string()))?;
+            let (replay, keep) = dead_letters.drain(..).partition(|dead_letter| {
+                message_id.is_none() || Some(dead_letter.message_id.as_str()) == message_id
+            });
+            *dead_letters = keep;
+            replay
+        };
+
+        let mut replayed = 0;
+        for dead_letter in replay {
+            if let Some(message) = dead_letter.message {
+                self.send(Job {
+                    message_id: dead_letter.message_id,
+                    message,
+                    attempts: 0,
+                })?;
+                replayed += 1;
+            }
+        }
+
+        Ok(replayed)
+    }
+}
+
+async fn handle_job(
+    job: Job,
+    pool: &PgPool,
+    sender: UnboundedSender<Job>,
+    dead_letters: &Mutex<Vec<DeadLetter>>,
+) {
+    tracing::info!("Received message: {:?}", job.message);
+
+    let Err(e) = verify_message(pool, &job.message).await else {
+        return;
+    };
+
+    let attempts = job.attempts + 1;
+    TracingError!(error = ?e, attempt = attempts, "Payment verification failed");
+
+    if attempts >= MAX_VERIFICATION_ATTEMPTS {
+        tracing::warn!(
+            "Verification dead-lettered after {} attempts: {}",
+            attempts,
+            e
+        );
+        if let Ok(mut dead_letters) = dead_letters.lock() {
+            dead_letters.push(DeadLetter {
+                message_id: job.message_id,
+                attempts,
+                last_error: Some(e.to_string()),
+                dead_lettered_at: Some(Utc::now()),
+                message: Some(job.message),
+            });
+        }
+        return;
+    }
+
+    let delay = retry_delay(attempts);
+    tracing::info!(
+        "Verification retry {} scheduled in {}ms",
+        attempts,
+        delay.as_millis()
+    );
+    tokio::time::sleep(delay).await;
+    let _ = sender.send(Job { attempts, ..job });
+}
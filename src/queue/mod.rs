@@ -0,0 +1,3 @@
+pub mod memory;
+pub mod postgres;
+pub mod queue;
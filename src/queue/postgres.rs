@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use tracing::error as TracingError;
+use uuid::Uuid;
+
+use crate::{
+    db::migrations::verification_jobs::{
+        inserts_and_updates::{
+            ADD_VERIFICATION_JOB, DEAD_LETTER_VERIFICATION_JOB, DELETE_VERIFICATION_JOB,
+            REPLAY_VERIFICATION_JOBS, RETRY_VERIFICATION_JOB,
+        },
+        select_queries::{CLAIM_VERIFICATION_JOB, GET_DEAD_LETTERED_VERIFICATION_JOBS},
+    },
+    error::StabuseError,
+    queue::queue::{retry_delay, verify_message, VerificationQueue, MAX_VERIFICATION_ATTEMPTS},
+    types::types::{DeadLetter, TransactionVerificationMessage, VerificationJob},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WORKERS: usize = 2;
+const JOB_LEASE: Duration = Duration::from_secs(300);
+
+/// Keeps verification jobs in the `verification_jobs` table. Workers claim
+/// a due job by pushing its `run_at` out by a lease, then verify it without
+/// holding a connection, so several server instances can share the table
+/// and a job whose worker dies comes due again once the lease runs out.
+pub struct PostgresQueue {
+    pool: PgPool,
+}
+
+impl PostgresQueue {
+    pub fn new(pool: PgPool) -> Self {
+        PostgresQueue { pool }
+    }
+}
+
+#[async_trait]
+impl VerificationQueue for PostgresQueue {
+    async fn publish(&self, message: &TransactionVerificationMessage) -> Result<(), StabuseError> {
+        sqlx::query(ADD_VERIFICATION_JOB)
+            .bind(Uuid::new_v4().to_string())
+            .bind(serde_json::to_string(message)?)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!("Message sent: {:?}", message);
+        Ok(())
+    }
+
+    async fn run_consumer(&self, pool: PgPool) {
+        tracing::info!("Polling verification_jobs with {} workers", WORKERS);
+
+        let workers = (0..WORKERS).map(|_| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                loop {
+                    match run_next_job(&pool).await {
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        Err(err) => TracingError!(error = ?err, "Error running verification job"),
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            })
+        });
+        join_all(workers).await;
+    }
+
+    async fn list_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>, StabuseError> {
+        let jobs = sqlx::query_as::<_, VerificationJob>(GET_DEAD_LETTERED_VERIFICATION_JOBS)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs
+            .into_iter()
+            .map(|job| DeadLetter {
+                message: serde_json::from_str(&job.payload).ok(),
+                message_id: job.message_id,
+                attempts: job.attempts as u32,
+                last_error: job.last_error,
+                dead_lettered_at: job.dead_lettered_at,
+            })
+            .collect())
+    }
+
+    async fn replay_dead_letters(&self, message_id: Option<&str>) -> Result<usize, StabuseError> {
+        let result = sqlx::query(REPLAY_VERIFICATION_JOBS)
+            .bind(message_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+}
+
+/// Claims and runs one due job. Returns `false` when there was none.
+async fn run_next_job(pool: &PgPool) -> Result<bool, StabuseError> {
+    let Some(job) = sqlx::query_as::<_, VerificationJob>(CLAIM_VERIFICATION_JOB)
+        .bind(JOB_LEASE.as_secs_f64())
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(false);
+    };
+    tracing::info!("Received message: {}", job.payload);
+
+    let outcome = match serde_json::from_str::<TransactionVerificationMessage>(&job.payload) {
+        Ok(message) => Ok(verify_message(pool, &message).await),
+        Err(e) => Err(e),
+    };
+
+    // The job is finished in a short transaction of its own, so no
+    // connection is held while the RPC endpoints are being asked.
+    let mut tx = pool.begin().await?;
+    let attempts = job.attempts as u32;
+    match outcome {
+        Ok(Ok(())) => {
+            sqlx::query(DELETE_VERIFICATION_JOB)
+                .bind(job.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        Ok(Err(e)) => {
+            let attempts = attempts + 1;
+            TracingError!(error = ?e, attempt = attempts, "Payment verification failed");
+            if attempts >= MAX_VERIFICATION_ATTEMPTS {
+                dead_letter(&mut tx, &job, attempts, &e.to_string()).await?;
+            } else {
+                let delay = retry_delay(attempts);
+                sqlx::query(RETRY_VERIFICATION_JOB)
+                    .bind(job.id)
+                    .bind(attempts as i32)
+                    .bind(e.to_string())
+                    .bind(delay.as_secs_f64())
+                    .execute(&mut *tx)
+                    .await?;
+                tracing::info!(
+                    "Verification retry {} scheduled in {}ms",
+                    attempts,
+                    delay.as_millis()
+                );
+            }
+        }
+        Err(e) => {
+            // Malformed payloads never succeed, so they skip the retries.
+            let reason = format!("Failed to deserialize message: {}", e);
+            dead_letter(&mut tx, &job, attempts, &reason).await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+async fn dead_letter(
+    tx: &mut Transaction<'_, Postgres>,
+    job: &VerificationJob,
+    attempts: u32,
+    reason: &str,
+) -> Result<(), StabuseError> {
+    sqlx::query(DEAD_LETTER_VERIFICATION_JOB)
+        .bind(job.id)
+        .bind(attempts as i32)
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+
+    tracing::warn!(
+        "Verification dead-lettered after {} attempts: {}",
+        attempts,
+        reason
+    );
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    core::{adapter::adapter::chain_adapter, rpc::rpc::with_failover},
+    error::StabuseError,
+    mq::mq::RabbitMqQueue,
+    network::network::get_network,
+    queue::{memory::InMemoryQueue, postgres::PostgresQueue},
+    types::types::{DeadLetter, TransactionVerificationMessage},
+};
+
+/// Verifications that fail this many times are dead-lettered.
+pub const MAX_VERIFICATION_ATTEMPTS: u32 = 5;
+/// Delay before the first retry; each further retry waits twice as long.
+pub const RETRY_BASE_DELAY: Duration = Duration::from_secs(5);
+
+/// Carries verification jobs from the API and the chain watcher to the
+/// consumer. Every backend retries a failed verification after
+/// `retry_delay(attempts)` and dead-letters it once it has failed
+/// `MAX_VERIFICATION_ATTEMPTS` times.
+#[async_trait]
+pub trait VerificationQueue: Send + Sync {
+    /// Enqueues a verification. Fails with `StabuseError::ServiceUnavailable`
+    /// when the backend cannot take it right now.
+    async fn publish(&self, message: &TransactionVerificationMessage) -> Result<(), StabuseError>;
+
+    /// Consumes verifications for the lifetime of the server.
+    async fn run_consumer(&self, pool: PgPool);
+
+    /// Lists up to `limit` dead-lettered verifications without removing them.
+    async fn list_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>, StabuseError>;
+
+    /// Puts dead-lettered verifications back on the queue with a fresh
+    /// attempt count: the one with `message_id`, or all of them when `None`.
+    /// Returns how many were replayed.
+    async fn replay_dead_letters(&self, message_id: Option<&str>) -> Result<usize, StabuseError>;
+}
+
+/// How long to wait before retrying a verification that has failed
+/// `attempts` times.
+pub fn retry_delay(attempts: u32) -> Duration {
+    RETRY_BASE_DELAY * 2u32.pow(attempts.saturating_sub(1))
+}
+
+/// Builds the backend named by `VERIFICATION_QUEUE`: `rabbitmq` (the
+/// default), `postgres` or `memory`. Only `rabbitmq` needs `RABBITMQ_URL`
+/// and `QUEUE_NAME`.
+pub fn verification_queue_from_env(
+    pool: &PgPool,
+) -> Result<Arc<dyn VerificationQueue>, StabuseError> {
+    let backend = std::env::var("VERIFICATION_QUEUE").unwrap_or_else(|_| "rabbitmq".to_string());
+
+    match backend.to_lowercase().as_str() {
+        "rabbitmq" => {
+            let rabbitmq_url = std::env::var("RABBITMQ_URL")
+                .map_err(|e| StabuseError::EnvError(format!("RABBITMQ_URL: {}", e)))?;
+            let queue_name = std::env::var("QUEUE_NAME")
+                .map_err(|e| StabuseError::EnvError(format!("QUEUE_NAME: {}", e)))?;
+            Ok(Arc::new(RabbitMqQueue::new(rabbitmq_url, queue_name)))
+        }
+        "postgres" => Ok(Arc::new(PostgresQueue::new(pool.clone()))),
+        "memory" => Ok(Arc::new(InMemoryQueue::new())),
+        other => Err(StabuseError::EnvError(format!(
+            "Unknown VERIFICATION_QUEUE backend: {}",
+            other
+        ))),
+    }
+}
+
+/// Verifies one message against its network. Shared by every backend's
+/// consumer; an error means the verification should be retried.
+pub async fn verify_message(
+    pool: &PgPool,
+    message: &TransactionVerificationMessage,
+) -> Result<(), StabuseError> {
+    let network = get_network(pool, message.chain_id).await?;
+    let adapter = chain_adapter(network.chain_family);
+    let (network_ref, tx_hash) = (&network, message.tx_hash.as_str());
+
+    with_failover(&network, |rpc_url| async move {
+        adapter
+            .verify_transaction(
+                pool,
+                network_ref,
+                &rpc_url,
+                message.pending_payment_id,
+                tx_hash,
+            )
+            .await
+    })
+    .await?;
+
+    tracing::info!(
+        "Transaction {} verified for pending payment {}",
+        message.tx_hash,
+        message.pending_payment_id
+    );
+    Ok(())
+}
@@ -1,26 +1,52 @@
 use crate::{
-    auth::jwt::{admin_jwt_validator, merchant_jwt_validator, pending_payment_jwt_validator},
+    auth::{
+        api_key::{
+            merchant_jwt_or_api_key_create_validator, merchant_jwt_or_api_key_validator,
+            payment_api_key_validator,
+        },
+        jwt::{admin_jwt_validator, merchant_jwt_validator, pending_payment_jwt_validator},
+    },
     handlers::{
         admin_handlers::{
             admin_login_handler, create_admin_with_invite_handler, create_super_admin_handler,
-            generate_admin_invite_handler, verify_otp_handler,
+            generate_admin_invite_handler, list_dead_letters_handler, replay_dead_letters_handler,
+            verify_otp_handler,
+        },
+        api_key_handlers::{create_api_key_handler, get_api_keys_handler, revoke_api_key_handler},
+        checkout_handlers::{
+            create_checkout_session_handler, get_checkout_session_handler,
+            select_checkout_option_handler,
         },
         handle_init_bd,
+        invoice_handlers::{
+            create_invoice_handler, get_merchant_invoice_handler, get_merchant_invoices_handler,
+        },
         merchant_handlers::{
             add_merchant_asset_handler, add_merchant_network_handler,
-            create_merchant_account_handler, merchant_login_handler, remove_merchant_asset_handler,
-            update_merchant_network_address_handler,
+            create_merchant_account_handler, get_webhook_secret_handler, merchant_login_handler,
+            remove_merchant_asset_handler, rotate_webhook_secret_handler,
+            update_merchant_network_address_handler, update_payment_expiry_handler,
+            update_payment_tolerance_handler, verify_webhook_signature_handler,
         },
         network_handler::{
             handle_add_asset, handle_add_network, handle_get_all_networks, handle_get_network,
-            handle_get_network_supported_assets, health_check,
+            handle_get_network_supported_assets, handle_update_asset_status,
+            handle_update_confirmation_policy, handle_update_rpc_endpoints, health_check,
         },
         payment_handlers::{
-            confirm_payment_transaction, create_payment_request_handler, validate_payment_handler,
+            confirm_payment_transaction, create_payment_request_handler,
+            get_merchant_payment_by_tx_hash_handler, get_merchant_payment_handler,
+            get_merchant_payments_handler, payment_status_handler, payment_status_stream_handler,
+            validate_payment_handler,
+        },
+        webhook_handlers::{
+            add_webhook_endpoint_handler, get_webhook_deliveries_handler,
+            get_webhook_delivery_handler, get_webhook_endpoints_handler, redeliver_webhook_handler,
+            remove_webhook_endpoint_handler, update_webhook_endpoint_handler,
         },
     },
 };
-use actix_web::web;
+use actix_web::{guard, web};
 use actix_web_httpauth::middleware::HttpAuthentication;
 pub fn configure_public_routes(cfg: &mut web::ServiceConfig) {
     cfg.route("/initdb", web::post().to(handle_init_bd))
@@ -43,6 +69,9 @@ pub fn configure_public_routes(cfg: &mut web::ServiceConfig) {
 
 pub fn configure_merchant_api_routes(cfg: &mut web::ServiceConfig) {
     let auth = HttpAuthentication::bearer(merchant_jwt_validator);
+    let payments_auth = HttpAuthentication::bearer(merchant_jwt_or_api_key_validator);
+    let invoices_auth = HttpAuthentication::bearer(merchant_jwt_or_api_key_validator);
+    let create_auth = HttpAuthentication::bearer(merchant_jwt_or_api_key_create_validator);
 
     cfg.service(
         web::scope("/api")
@@ -54,6 +83,35 @@ pub fn configure_merchant_api_routes(cfg: &mut web::ServiceConfig) {
                         web::post().to(create_merchant_account_handler),
                     ),
             )
+            // Registered ahead of `/merchant`, which would otherwise claim these paths.
+            .service(
+                web::scope("/merchant/payments")
+                    .wrap(payments_auth)
+                    .route("", web::get().to(get_merchant_payments_handler))
+                    .route(
+                        "/tx/{tx_hash}",
+                        web::get().to(get_merchant_payment_by_tx_hash_handler),
+                    )
+                    .route("/{id}", web::get().to(get_merchant_payment_handler)),
+            )
+            // Guarded on POST, so reads fall through to the invoices scope.
+            .service(
+                web::resource("/merchant/invoices")
+                    .guard(guard::Post())
+                    .wrap(create_auth.clone())
+                    .route(web::post().to(create_invoice_handler)),
+            )
+            .service(
+                web::scope("/merchant/checkout-sessions")
+                    .wrap(create_auth)
+                    .route("", web::post().to(create_checkout_session_handler)),
+            )
+            .service(
+                web::scope("/merchant/invoices")
+                    .wrap(invoices_auth)
+                    .route("", web::get().to(get_merchant_invoices_handler))
+                    .route("/{invoice_id}", web::get().to(get_merchant_invoice_handler)),
+            )
             .service(
                 web::scope("/merchant")
                     .wrap(auth)
@@ -72,7 +130,55 @@ pub fn configure_merchant_api_routes(cfg: &mut web::ServiceConfig) {
                     .route(
                         "/updateaddress",
                         web::post().to(update_merchant_network_address_handler),
-                    ),
+                    )
+                    .route(
+                        "/updatepaymentexpiry",
+                        web::post().to(update_payment_expiry_handler),
+                    )
+                    .route(
+                        "/updatepaymenttolerance",
+                        web::post().to(update_payment_tolerance_handler),
+                    )
+                    .route("/webhooksecret", web::get().to(get_webhook_secret_handler))
+                    .route(
+                        "/rotatewebhooksecret",
+                        web::post().to(rotate_webhook_secret_handler),
+                    )
+                    .route(
+                        "/verifywebhooksignature",
+                        web::post().to(verify_webhook_signature_handler),
+                    )
+                    .route(
+                        "/webhookendpoints",
+                        web::get().to(get_webhook_endpoints_handler),
+                    )
+                    .route(
+                        "/addwebhookendpoint",
+                        web::post().to(add_webhook_endpoint_handler),
+                    )
+                    .route(
+                        "/updatewebhookendpoint",
+                        web::post().to(update_webhook_endpoint_handler),
+                    )
+                    .route(
+                        "/removewebhookendpoint",
+                        web::post().to(remove_webhook_endpoint_handler),
+                    )
+                    .route(
+                        "/webhookdeliveries",
+                        web::get().to(get_webhook_deliveries_handler),
+                    )
+                    .route(
+                        "/webhookdelivery",
+                        web::get().to(get_webhook_delivery_handler),
+                    )
+                    .route(
+                        "/redeliverwebhook",
+                        web::post().to(redeliver_webhook_handler),
+                    )
+                    .route("/apikeys", web::get().to(get_api_keys_handler))
+                    .route("/createapikey", web::post().to(create_api_key_handler))
+                    .route("/revokeapikey", web::post().to(revoke_api_key_handler)),
             ),
     );
 }
@@ -95,20 +201,53 @@ pub fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
                         web::post().to(generate_admin_invite_handler),
                     )
                     .route("/addnetwork", web::post().to(handle_add_network))
-                    .route("/addasset", web::post().to(handle_add_asset)),
+                    .route("/addasset", web::post().to(handle_add_asset))
+                    .route(
+                        "/updateassetstatus",
+                        web::post().to(handle_update_asset_status),
+                    )
+                    .route(
+                        "/updateconfirmationpolicy",
+                        web::post().to(handle_update_confirmation_policy),
+                    )
+                    .route(
+                        "/updaterpcendpoints",
+                        web::post().to(handle_update_rpc_endpoints),
+                    )
+                    .route("/deadletters", web::get().to(list_dead_letters_handler))
+                    .route(
+                        "/deadletters/replay",
+                        web::post().to(replay_dead_letters_handler),
+                    ),
             ),
     );
 }
 
 pub fn configure_payment_routes(cfg: &mut web::ServiceConfig) {
     let auth = HttpAuthentication::bearer(pending_payment_jwt_validator);
+    let api_key_auth = HttpAuthentication::bearer(payment_api_key_validator);
 
     cfg.service(
         web::scope("/user")
-            .service(web::scope("/auth").route(
+            .service(web::scope("/auth").wrap(api_key_auth).route(
                 "/make-payment",
                 web::post().to(create_payment_request_handler),
             ))
+            // The session id is the payer's credential here.
+            .service(
+                web::scope("/checkout")
+                    .route("/{session_id}", web::get().to(get_checkout_session_handler))
+                    .route(
+                        "/{session_id}/pay",
+                        web::post().to(select_checkout_option_handler),
+                    ),
+            )
+            // Authenticated by the handler, which also takes the token from
+            // the query string.
+            .route(
+                "/payment-status/stream",
+                web::get().to(payment_status_stream_handler),
+            )
             .service(
                 web::scope("")
                     .wrap(auth)
@@ -116,7 +255,8 @@ pub fn configure_payment_routes(cfg: &mut web::ServiceConfig) {
                     .route(
                         "/tx-payment/{tx_hash}",
                         web::get().to(confirm_payment_transaction),
-                    ),
+                    )
+                    .route("/payment-status", web::get().to(payment_status_handler)),
             ),
     );
 }
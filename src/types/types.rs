@@ -1,24 +1,97 @@
-use alloy::primitives::{Address, U256};
+use alloy::primitives::Address;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, types::Json};
 use std::collections::HashMap;
 
+use crate::error::StabuseError;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Network {
     pub chain_id: i64,
     pub name: String,
+    pub chain_family: ChainFamily,
     pub explorer: String,
     pub rpc: String,
     pub supported_assets: HashMap<String, String>,
+    pub confirmation_policy: Option<ConfirmationPolicy>,
+    pub rpc_endpoints: Option<Vec<RpcEndpoint>>,
+    pub rpc_quorum: Option<u32>,
+    /// Testnets only take test mode payments; defaults to `false`.
+    pub is_testnet: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize, FromRow)]
-pub struct NetworkInfo {
-    pub name: String,
-    pub supported_assets: Value,
+/// The kind of chain a network runs on. It picks the `ChainAdapter` used for
+/// every RPC call against the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ChainFamily {
+    Evm,
+    Solana,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RpcEndpoint {
+    pub url: String,
+    pub weight: u32,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRpcEndpointsRequest {
+    pub chain_id: i64,
+    pub rpc_endpoints: Vec<RpcEndpoint>,
+    pub rpc_quorum: Option<u32>,
+}
+
+/// How deep a transaction must be before a payment on a network is confirmed.
+/// `depth` and `finalized` apply to EVM chains, `confirmed` and `finalized`
+/// map to Solana commitment levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum ConfirmationPolicy {
+    Depth { blocks: u64 },
+    Finalized,
+    Confirmed,
+}
+
+impl ConfirmationPolicy {
+    pub fn from_columns(mode: &str, depth: i64) -> Result<Self, StabuseError> {
+        match mode {
+            "depth" => Ok(ConfirmationPolicy::Depth {
+                blocks: depth as u64,
+            }),
+            "finalized" => Ok(ConfirmationPolicy::Finalized),
+            "confirmed" => Ok(ConfirmationPolicy::Confirmed),
+            _ => Err(StabuseError::Internal(format!(
+                "Unknown confirmation mode: {}",
+                mode
+            ))),
+        }
+    }
+
+    pub fn mode(&self) -> &'static str {
+        match self {
+            ConfirmationPolicy::Depth { .. } => "depth",
+            ConfirmationPolicy::Finalized => "finalized",
+            ConfirmationPolicy::Confirmed => "confirmed",
+        }
+    }
+
+    pub fn depth(&self) -> i64 {
+        match self {
+            ConfirmationPolicy::Depth { blocks } => *blocks as i64,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateConfirmationPolicyRequest {
+    pub chain_id: i64,
+    pub confirmation_policy: ConfirmationPolicy,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -26,8 +99,15 @@ pub struct NetworkDB {
     pub id: i32,
     pub chain_id: i64,
     pub name: String,
+    pub chain_family: ChainFamily,
+    /// EIP-155 chain ID for EVM networks, genesis hash for Solana.
+    pub chain_identifier: String,
+    pub is_testnet: bool,
     pub rpc_url: String,
-    pub supported_assets: HashMap<String, String>,
+    pub supported_assets: Vec<NetworkAsset>,
+    pub confirmation_policy: ConfirmationPolicy,
+    pub rpc_endpoints: Vec<RpcEndpoint>,
+    pub rpc_quorum: u32,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -40,14 +120,33 @@ pub struct Merchant {
     pub supported_networks: HashMap<i32, String>, // Map the network chain ID to the client address
 }
 
-pub type Asset = HashMap<String, String>;
+/// A token registered on a network. `decimals` and `symbol` are read from the
+/// ERC-20 contract or SPL mint when the asset is added.
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow)]
+pub struct NetworkAsset {
+    pub id: i32,
+    pub chain_id: i64,
+    pub ticker: String,
+    pub address: String,
+    #[sqlx(try_from = "i16")]
+    pub decimals: u8,
+    pub symbol: String,
+    pub enabled: bool,
+}
 
 #[derive(Deserialize)]
 pub struct AddAssetRequest {
-    pub chain_id: i32,
+    pub chain_id: i64,
     pub assets: HashMap<String, String>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateAssetStatusRequest {
+    pub chain_id: i64,
+    pub ticker: String,
+    pub enabled: bool,
+}
+
 #[derive(Deserialize)]
 pub struct CreateMerchantRequest {
     pub username: String,
@@ -76,6 +175,166 @@ pub struct MerchantNetworkRequest {
     pub address: String,
 }
 
+#[derive(Deserialize)]
+pub struct PaymentExpiryRequest {
+    /// Minutes a payment stays open; `null` restores the default.
+    pub payment_expiry_minutes: Option<i32>,
+}
+
+/// Fractions of the requested amount (`0.01` = 1%) a merchant accepts as
+/// paid in full despite receiving less, or without flagging an overpayment.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PaymentTolerance {
+    pub underpayment_tolerance: BigDecimal,
+    pub overpayment_tolerance: BigDecimal,
+}
+
+/// The secrets a merchant's webhooks are signed with. After a rotation the
+/// previous secret keeps signing alongside the new one until it expires.
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookSecrets {
+    pub webhook_secret: String,
+    pub previous_webhook_secret: Option<String>,
+    pub previous_webhook_secret_expires_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookSecrets {
+    /// The secrets webhooks are currently signed with, newest first.
+    pub fn signing_secrets(&self) -> Vec<&str> {
+        let mut secrets = vec![self.webhook_secret.as_str()];
+        if let (Some(previous), Some(expires_at)) = (
+            self.previous_webhook_secret.as_deref(),
+            self.previous_webhook_secret_expires_at,
+        ) {
+            if expires_at > Utc::now() {
+                secrets.push(previous);
+            }
+        }
+        secrets
+    }
+}
+
+/// A URL on the merchant's side that payment events are delivered to.
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookEndpoint {
+    pub id: i32,
+    pub mode: AccountMode,
+    pub url: String,
+    /// Events the endpoint receives, e.g. `payment.confirmed`; empty for all.
+    pub event_types: Vec<String>,
+    pub enabled: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookEndpointRequest {
+    pub url: String,
+    /// Subscribes to every event when omitted or empty.
+    pub event_types: Option<Vec<String>>,
+    /// Which mode's events the endpoint receives; defaults to live.
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateWebhookEndpointRequest {
+    pub id: i32,
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveWebhookEndpointRequest {
+    pub id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    /// Gave up after the retry cap; only a manual redelivery tries again.
+    Failed,
+}
+
+/// One event's delivery to one endpoint, as listed to merchants.
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_event_id: i32,
+    pub webhook_endpoint_id: i32,
+    pub mode: AccountMode,
+    pub url: String,
+    pub event_type: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookDeliveryDetails {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    pub payload: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookDeliveryAttempt {
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub latency_ms: i32,
+    /// Truncated to the first few hundred characters.
+    pub response_body: Option<String>,
+    /// Set when no response was received at all.
+    pub error: Option<String>,
+    pub time: DateTime<Utc>,
+}
+
+/// A delivery claimed by the delivery worker.
+#[derive(Debug, FromRow)]
+pub struct DueWebhookDelivery {
+    pub id: i32,
+    pub attempts: i32,
+    pub merchant_id: i32,
+    pub payload: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub endpoint_id: Option<i32>,
+    pub status: Option<WebhookDeliveryStatus>,
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookDeliveryRequest {
+    pub delivery_id: i32,
+}
+
+#[derive(Deserialize)]
+pub struct RotateWebhookSecretRequest {
+    /// Minutes the old secret keeps signing webhooks; defaults to a day,
+    /// `0` revokes it immediately.
+    pub overlap_minutes: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyWebhookSignatureRequest {
+    /// The webhook body exactly as received.
+    pub payload: String,
+    /// The `Stabuse-Signature` header value.
+    pub signature: String,
+}
+
 #[derive(Deserialize)]
 pub struct MerchantAddressRequest {
     pub chain_id: i64,
@@ -109,11 +368,18 @@ pub struct AdminClaims {
 pub struct PaymentClaims {
     pub pending_payment_id: i32,
     pub network: String,
-    pub rpc: String,
+    pub chain_id: i64,
     pub exp: i64, // expiration timestamp
     pub iat: i64, // issued at timestamp
 }
 
+/// A pending payment token passed in the query string, for clients such as
+/// `EventSource` that cannot set an `Authorization` header.
+#[derive(Deserialize)]
+pub struct PaymentTokenQuery {
+    pub token: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct MerchantCredentials {
     pub id: i32,
@@ -173,31 +439,68 @@ pub struct Stablecoins {
     pub dai: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A settled payment, as listed to the merchant it was paid to.
+#[derive(Debug, Serialize, FromRow)]
 pub struct Payment {
+    pub id: i32,
+    pub pending_payment_id: Option<i32>,
     pub sender: String,
-    pub amount: u64,
+    pub amount: BigDecimal,
+    pub amount_received: BigDecimal,
     pub tx_hash: String,
     pub asset: String,
     pub network: String,
-    pub time: NaiveDateTime,
+    pub mode: AccountMode,
+    pub status: PaymentStatus,
+    pub invoice_id: Option<String>,
+    pub external_order_id: Option<String>,
+    pub time: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct PaymentHistoryQuery {
+    /// The `next_cursor` of the previous page.
+    pub cursor: Option<i32>,
+    pub limit: Option<i64>,
+    pub network: Option<String>,
+    pub asset: Option<String>,
+    pub status: Option<PaymentStatus>,
+    pub sender: Option<String>,
+    /// Inclusive lower bound on the payment time.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the payment time.
+    pub to: Option<DateTime<Utc>>,
+    /// Defaults to live; API keys always see their own mode.
+    pub mode: Option<AccountMode>,
+    pub invoice_id: Option<String>,
+    /// The merchant's order id on the invoice the payment was made against.
+    pub external_order_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PaymentHistoryPage {
+    pub payments: Vec<Payment>,
+    /// Pass as `cursor` to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<i32>,
+    /// Payments matching the filters across all pages.
+    pub total: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreatePaymentRequest {
-    pub merchant_id: i32,
-    pub payment_amount: u64,
+    /// Amount in whole tokens, e.g. "12.50". Sent as a string so it is not
+    /// rounded through a float on the way in.
+    pub payment_amount: BigDecimal,
     pub user_address: String,
     pub asset: String,
-    pub rpc_url: String,
-    pub network: String,
+    pub chain_id: i64,
+    /// An open invoice to pay. `payment_amount` must be its total.
+    pub invoice_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ValidatePaymentRequest {
     pub tx_hash: String,
-    pub rpc_url: String,
-    pub network: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -221,7 +524,87 @@ pub struct TransactionValidationParams {
     pub merchant_address: Address,
     pub token_address: Address,
     pub user_address: Address,
-    pub amount: U256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentStatus {
+    Created,
+    Underpaid,
+    Seen,
+    Confirming,
+    Confirmed,
+    Overpaid,
+    Reorged,
+    Failed,
+    Expired,
+}
+
+impl PaymentStatus {
+    pub const ALL: [PaymentStatus; 9] = [
+        PaymentStatus::Created,
+        PaymentStatus::Underpaid,
+        PaymentStatus::Seen,
+        PaymentStatus::Confirming,
+        PaymentStatus::Confirmed,
+        PaymentStatus::Overpaid,
+        PaymentStatus::Reorged,
+        PaymentStatus::Failed,
+        PaymentStatus::Expired,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Created => "created",
+            PaymentStatus::Underpaid => "underpaid",
+            PaymentStatus::Seen => "seen",
+            PaymentStatus::Confirming => "confirming",
+            PaymentStatus::Confirmed => "confirmed",
+            PaymentStatus::Overpaid => "overpaid",
+            PaymentStatus::Reorged => "reorged",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Expired => "expired",
+        }
+    }
+
+    /// Whether the payment can no longer change state.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Confirmed | PaymentStatus::Overpaid | PaymentStatus::Expired
+        )
+    }
+}
+
+/// Every merchant has a test and a live environment. The mode comes from
+/// the API key a payment was created with, and keeps test payments and
+/// webhooks apart from live ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccountMode {
+    Test,
+    #[default]
+    Live,
+}
+
+impl AccountMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountMode::Test => "test",
+            AccountMode::Live => "live",
+        }
+    }
+
+    /// Test mode runs on testnets only, live mode on mainnets only.
+    pub fn for_network(network: &NetworkDB) -> Self {
+        if network.is_testnet {
+            AccountMode::Test
+        } else {
+            AccountMode::Live
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -230,30 +613,365 @@ pub struct PendingPayment {
     pub merchant_id: i32,
     pub sender: String,
     pub amount: BigDecimal,
+    pub amount_received: BigDecimal,
     pub asset: String,
     pub network: String,
-    pub webhook_url: String,
+    pub mode: AccountMode,
+    pub invoice_id: Option<String>,
+    pub status: PaymentStatus,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub block_hash: Option<String>,
+    pub confirmations: i64,
+    pub failure_reason: Option<String>,
+    pub expires_at: DateTime<Utc>,
     pub time: NaiveDateTime,
 }
 
+#[derive(Debug, FromRow)]
+pub struct AwaitingConfirmation {
+    #[sqlx(flatten)]
+    pub pending_payment: PendingPayment,
+    pub chain_id: i64,
+}
+
+/// One on-chain transfer counted towards a pending payment. A payment paid
+/// in several transfers is only as confirmed as the shallowest of them.
+#[derive(Debug, FromRow)]
+pub struct PaymentTransfer {
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub block_hash: String,
+}
+
+#[derive(Debug)]
+pub struct TransactionInclusion {
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
+#[derive(Debug)]
+pub enum ConfirmationCheck {
+    Confirmations { confirmations: u64, satisfied: bool },
+    Moved(TransactionInclusion),
+    Dropped,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PendingPaymentMatch {
+    pub id: i32,
+    pub sender: String,
+    pub asset: String,
+    pub merchant_address: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TransactionVerificationMessage {
     pub pending_payment_id: i32,
     pub tx_hash: String,
-    pub rpc_url: String,
+    pub chain_id: i64,
     pub network: String,
 }
 
+/// A verification that exhausted its retries, as listed to admins.
+#[derive(Serialize, Clone, Debug)]
+pub struct DeadLetter {
+    pub message_id: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub dead_lettered_at: Option<DateTime<Utc>>,
+    /// `None` when the payload could not be parsed.
+    pub message: Option<TransactionVerificationMessage>,
+}
+
+/// A row of the Postgres-backed verification queue.
+#[derive(Debug, FromRow)]
+pub struct VerificationJob {
+    pub id: i32,
+    pub message_id: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub dead_lettered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct DeadLetterQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayDeadLettersRequest {
+    /// Replays every dead letter when omitted.
+    pub message_id: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PaymentAuthDetails {
+    pub pending_payment_id: i32,
     pub jwt_token: String,
-    pub webhook_url: String,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookPayload {
+    pub event: String,
     pub payment_id: i32,
-    pub status: String,
-    pub tx_hash: String,
+    pub mode: AccountMode,
+    pub status: PaymentStatus,
+    pub tx_hash: Option<String>,
+    pub confirmations: i64,
+    pub amount: BigDecimal,
+    pub amount_received: BigDecimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortfall: Option<BigDecimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excess: Option<BigDecimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<InvoiceReference>,
     pub timestamp: String,
 }
+
+/// What the checkout page sees of a pending payment. `required_confirmations`
+/// is only known for networks confirming by block depth.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingPaymentState {
+    pub id: i32,
+    pub mode: AccountMode,
+    pub status: PaymentStatus,
+    pub asset: String,
+    pub network: String,
+    pub amount: BigDecimal,
+    pub amount_received: BigDecimal,
+    pub tx_hash: Option<String>,
+    pub confirmations: i64,
+    pub required_confirmations: Option<u64>,
+    pub failure_reason: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PendingPaymentState {
+    pub fn apply(&mut self, update: &WebhookPayload) {
+        self.status = update.status;
+        self.tx_hash = update.tx_hash.clone();
+        self.confirmations = update.confirmations;
+        self.amount_received = update.amount_received.clone();
+        self.failure_reason = update.failure_reason.clone();
+    }
+}
+
+/// A merchant's publishable/secret key pair. Only the prefixes are kept in
+/// the clear; the keys themselves are shown once, when the pair is created.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    pub mode: AccountMode,
+    pub publishable_prefix: String,
+    pub secret_prefix: String,
+    pub scopes: Vec<String>,
+    pub publishable_last_used_at: Option<DateTime<Utc>>,
+    pub secret_last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub publishable_key: String,
+    pub secret_key: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ApiKeyCredentials {
+    pub id: i32,
+    pub merchant_id: i32,
+    pub mode: AccountMode,
+    pub publishable_key_hash: String,
+    pub secret_key_hash: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyKind {
+    Publishable,
+    Secret,
+}
+
+/// The merchant and key a request was authenticated with.
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub key_id: i32,
+    pub merchant_id: i32,
+    pub kind: ApiKeyKind,
+    pub mode: AccountMode,
+}
+
+#[derive(Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// Defaults to every scope.
+    pub scopes: Option<Vec<String>>,
+    /// Defaults to live.
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeApiKeyRequest {
+    pub key_id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CheckoutSessionStatus {
+    Open,
+    Completed,
+    Expired,
+}
+
+/// A payment request for an amount and order, before the payer has picked
+/// how to pay it. `session_id` is the only handle the payer gets.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CheckoutSession {
+    pub session_id: String,
+    #[serde(skip)]
+    pub merchant_id: i32,
+    pub mode: AccountMode,
+    pub amount: BigDecimal,
+    pub order_reference: String,
+    pub invoice_id: Option<String>,
+    pub status: CheckoutSessionStatus,
+    /// The payment for the payer's latest choice of network and asset.
+    pub pending_payment_id: Option<i32>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A network and asset the merchant accepts, and so a payer can choose.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CheckoutOption {
+    pub chain_id: i64,
+    pub network: String,
+    pub asset: String,
+    pub symbol: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCheckoutSessionRequest {
+    /// Amount in whole tokens, sent as a string like `payment_amount`.
+    pub amount: BigDecimal,
+    pub order_reference: String,
+    /// An open invoice the session collects. `amount` must be its total.
+    pub invoice_id: Option<String>,
+    /// Defaults to an hour.
+    pub expires_in_minutes: Option<i32>,
+    /// Defaults to live; API keys always use their own mode.
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Deserialize)]
+pub struct CheckoutSelectionRequest {
+    pub chain_id: i64,
+    pub asset: String,
+    pub payer_address: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceStatus {
+    Open,
+    Paid,
+}
+
+/// What a merchant bills for, in its own currency and order numbering.
+/// Payments made against an invoice carry its `invoice_id`, which is the
+/// only id merchants and payers see.
+#[derive(Debug, Serialize, FromRow)]
+pub struct Invoice {
+    #[serde(skip)]
+    pub id: i32,
+    pub invoice_id: String,
+    pub mode: AccountMode,
+    pub external_order_id: Option<String>,
+    /// ISO 4217 code, e.g. "USD".
+    pub currency: String,
+    pub description: Option<String>,
+    pub line_items: Json<Vec<InvoiceLineItem>>,
+    pub subtotal: BigDecimal,
+    /// Percentage of the subtotal, e.g. "8.25".
+    pub tax_rate: Option<BigDecimal>,
+    pub tax_amount: BigDecimal,
+    pub total: BigDecimal,
+    pub metadata: Value,
+    pub status: InvoiceStatus,
+    pub created_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceLineItem {
+    pub description: String,
+    pub quantity: BigDecimal,
+    pub unit_amount: BigDecimal,
+    pub amount: BigDecimal,
+}
+
+/// The part of an invoice echoed in the webhooks of payments made against
+/// it, so merchants can match them to their own orders.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InvoiceReference {
+    pub invoice_id: String,
+    pub external_order_id: Option<String>,
+    pub metadata: Value,
+}
+
+#[derive(Deserialize)]
+pub struct InvoiceLineItemRequest {
+    pub description: String,
+    /// Defaults to one.
+    pub quantity: Option<BigDecimal>,
+    /// Price of one unit in the invoice currency, sent as a string.
+    pub unit_amount: BigDecimal,
+}
+
+#[derive(Deserialize)]
+pub struct CreateInvoiceRequest {
+    /// Only "USD" for now: payments are taken at par with it.
+    pub currency: String,
+    /// The merchant's own order id, unique per merchant and mode.
+    pub external_order_id: Option<String>,
+    pub description: Option<String>,
+    pub line_items: Vec<InvoiceLineItemRequest>,
+    /// Percentage of the subtotal. Give either this or `tax_amount`.
+    pub tax_rate: Option<BigDecimal>,
+    pub tax_amount: Option<BigDecimal>,
+    /// Any JSON object; stored and echoed back as is.
+    pub metadata: Option<Value>,
+    /// Defaults to live; API keys always use their own mode.
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Deserialize)]
+pub struct InvoiceQuery {
+    /// The `next_cursor` of the previous page.
+    pub cursor: Option<i32>,
+    pub limit: Option<i64>,
+    pub status: Option<InvoiceStatus>,
+    pub external_order_id: Option<String>,
+    /// Defaults to live; API keys always see their own mode.
+    pub mode: Option<AccountMode>,
+}
+
+#[derive(Serialize)]
+pub struct InvoicePage {
+    pub invoices: Vec<Invoice>,
+    /// Pass as `cursor` to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<i32>,
+}
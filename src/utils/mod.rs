@@ -1,2 +1,4 @@
+pub mod secret;
+pub mod signature;
 pub mod utils;
 pub mod validation;
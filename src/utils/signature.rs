@@ -0,0 +1,86 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::StabuseError;
+
+pub const SIGNATURE_HEADER: &str = "Stabuse-Signature";
+pub const TIMESTAMP_HEADER: &str = "Stabuse-Timestamp";
+
+/// How far a signature's timestamp may be from the verifier's clock before
+/// the webhook is treated as a replay.
+pub const DEFAULT_SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn signing_mac(secret: &str, timestamp: i64, body: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// Hex-encoded HMAC-SHA256 of `"{timestamp}.{body}"`.
+pub fn compute_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    hex::encode(signing_mac(secret, timestamp, body).finalize().into_bytes())
+}
+
+/// Builds the `Stabuse-Signature` header value, `t=<timestamp>,v1=<hex>`,
+/// with one `v1` entry per secret so webhooks sent while a rotation is in
+/// its overlap window verify against either the old or the new secret.
+pub fn signature_header(secrets: &[&str], timestamp: i64, body: &str) -> String {
+    let mut header = format!("t={}", timestamp);
+    for secret in secrets {
+        header.push_str(",v1=");
+        header.push_str(&compute_signature(secret, timestamp, body));
+    }
+    header
+}
+
+/// Checks a `Stabuse-Signature` header against the raw request body, the
+/// way merchant SDKs should: the timestamp must be within `tolerance_secs`
+/// of `now` and at least one `v1` entry must match, compared in constant
+/// time. Verify the body exactly as received; re-serialized JSON will not
+/// match.
+pub fn verify_webhook_signature(
+    secret: &str,
+    header: &str,
+    body: &str,
+    now: i64,
+    tolerance_secs: i64,
+) -> Result<(), StabuseError> {
+    let mut timestamp = None;
+    let mut signatures = vec![];
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or_else(|| {
+        StabuseError::Unauthorized("Signature header has no valid timestamp".to_string())
+    })?;
+    if (now - timestamp).abs() > tolerance_secs {
+        return Err(StabuseError::Unauthorized(
+            "Signature timestamp is outside the tolerance window".to_string(),
+        ));
+    }
+
+    let matches = signatures.iter().any(|signature| {
+        hex::decode(signature).is_ok_and(|bytes| {
+            signing_mac(secret, timestamp, body)
+                .verify_slice(&bytes)
+                .is_ok()
+        })
+    });
+    if !matches {
+        return Err(StabuseError::Unauthorized(
+            "No signature matches the payload".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -1,16 +1,19 @@
-use crate::error::StabuseError;
-use alloy::hex;
+use crate::{
+    error::StabuseError,
+    utils::signature::{signature_header, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+use alloy::primitives::U256;
 use bcrypt::{hash, DEFAULT_COST};
+use bigdecimal::{num_bigint::BigInt, BigDecimal, Signed, Zero};
 use chrono::Utc;
 use reqwest::Client;
-use serde_json::{self, Value};
-use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::env;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
 
 pub const MIN_PASSWORD_LENGTH: usize = 8;
 pub const MIN_USERNAME_LENGTH: usize = 3;
-const TOKEN_DECIMALS: &[(&str, u8)] = &[("USDC", 6), ("DAI", 18), ("USDT", 6), ("BUSD", 18)];
 
 pub fn transform_assets_to_uppercase(assets: &HashMap<String, String>) -> HashMap<String, String> {
     assets
@@ -19,76 +22,178 @@ pub fn transform_assets_to_uppercase(assets: &HashMap<String, String>) -> HashMa
         .collect()
 }
 
-pub fn hashmap_to_json_value(map: HashMap<String, String>) -> Result<Value, StabuseError> {
-    let value =
-        serde_json::to_value(map).map_err(|err| StabuseError::SerdeError(err.to_string()))?;
-
-    Ok(value)
-}
-
 pub fn hash_password(password: &str) -> Result<String, StabuseError> {
     let password_hash = hash(password, DEFAULT_COST).map_err(|e| StabuseError::HashError(e))?;
     Ok(password_hash)
 }
 
-pub fn get_token_decimals(asset: &str) -> Result<u8, StabuseError> {
-    TOKEN_DECIMALS
-        .iter()
-        .find(|(token, _)| *token == asset)
-        .map(|(_, decimals)| *decimals)
-        .ok_or_else(|| StabuseError::InvalidData(format!("Unsupported token: {}", asset)))
-}
+/// Scales a human-readable token amount (e.g. `12.5` USDC) into the token's
+/// base units. Amounts with more fractional digits than the token supports are
+/// rejected rather than rounded.
+pub fn to_base_units(amount: &BigDecimal, decimals: u8) -> Result<U256, StabuseError> {
+    if amount.is_negative() || amount.is_zero() {
+        return Err(StabuseError::InvalidData(
+            "Payment amount must be greater than zero".to_string(),
+        ));
+    }
 
-pub fn get_solana_network_identifier(rpc_url: &str) -> Result<i64, StabuseError> {
-    match rpc_url {
-        url if url.contains("mainnet") => {
-            return Ok(101);
-        }
-        url if url.contains("devnet") => {
-            return Ok(102);
-        }
-        url if url.contains("testnet") => {
-            return Ok(103);
-        }
-        _ => {
-            return Err(StabuseError::InvalidData(format!(
-                "RPC URL {} not recognized",
-                rpc_url
-            )));
-        }
-    };
-}
+    let (digits, scale) = amount.normalized().as_bigint_and_exponent();
+    if scale > decimals as i64 {
+        return Err(StabuseError::InvalidData(format!(
+            "Payment amount {} has more than {} decimal places",
+            amount, decimals
+        )));
+    }
 
-pub fn generate_webhook_url(merchant_id: i32, user_address: &str, amount: u64) -> (String, String) {
-    let base_webhook_url = env::var("WEBHOOK_BASE_URL").expect("WEBHOOK_BASE_URL must be set");
-    let timestamp = Utc::now().to_rfc3339();
+    let digits = U256::from_str_radix(&digits.to_string(), 10).map_err(|_| {
+        StabuseError::InvalidData(format!("Payment amount {} is too large", amount))
+    })?;
+    let multiplier = U256::from(10).pow(U256::from(decimals as i64 - scale));
 
-    let data = format!("{}:{}:{}:{}", merchant_id, user_address, amount, timestamp);
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    let result = hasher.finalize();
-    let hash_hex = hex::encode(result);
+    digits
+        .checked_mul(multiplier)
+        .ok_or_else(|| StabuseError::InvalidData(format!("Payment amount {} is too large", amount)))
+}
+
+/// Inverse of [`to_base_units`]: turns an on-chain amount back into the
+/// token's human-readable units.
+pub fn from_base_units(amount: U256, decimals: u8) -> BigDecimal {
+    let digits = BigInt::from_str(&amount.to_string()).unwrap_or_default();
+    BigDecimal::new(digits, decimals as i64).normalized()
+}
 
-    let webhook_url = format!("{}/{}", base_webhook_url, hash_hex);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Only the start of a webhook response is kept, so a receiver answering
+/// with a huge body cannot make us buffer all of it.
+const MAX_WEBHOOK_RESPONSE_BYTES: usize = 4 * 1024;
 
-    (webhook_url, timestamp)
+fn webhook_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .expect("Failed to build webhook HTTP client")
+    })
 }
 
-pub async fn send_webhook_notification(webhook_url: &str, data: &str) -> Result<(), StabuseError> {
-    let client = Client::new();
-    let res = client
+/// Posts `data` to the webhook, signed with every secret in
+/// `signing_secrets` (see `signature::signature_header`), and returns the
+/// response status and the first `MAX_WEBHOOK_RESPONSE_BYTES` of the body.
+/// Only a missing response is an error; callers decide what the status
+/// means.
+pub async fn send_webhook_notification(
+    webhook_url: &str,
+    data: &str,
+    signing_secrets: &[&str],
+) -> Result<(u16, String), StabuseError> {
+    let timestamp = Utc::now().timestamp();
+    let mut res = webhook_client()
         .post(webhook_url)
         .header("Content-Type", "application/json")
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            signature_header(signing_secrets, timestamp, data),
+        )
         .body(data.to_string())
         .send()
         .await
         .map_err(|e| StabuseError::Internal(format!("Webhook failed: {}", e)))?;
 
-    if res.status().is_success() {
-        tracing::info!("Webhook delivered successfully to {}", webhook_url);
-    } else {
-        tracing::info!("Message published successfully to webhook: {}", webhook_url);
+    let status = res.status().as_u16();
+    let mut body = Vec::new();
+    while body.len() < MAX_WEBHOOK_RESPONSE_BYTES {
+        match res.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    body.truncate(MAX_WEBHOOK_RESPONSE_BYTES);
+
+    Ok((status, String::from_utf8_lossy(&body).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn six_decimal_amounts_round_trip() {
+        let base = to_base_units(&amount("12.5"), 6).unwrap();
+        assert_eq!(base, U256::from(12_500_000u64));
+        assert_eq!(from_base_units(base, 6), amount("12.5"));
+
+        let base = to_base_units(&amount("0.000001"), 6).unwrap();
+        assert_eq!(base, U256::from(1u64));
+        assert_eq!(from_base_units(base, 6), amount("0.000001"));
+    }
+
+    #[test]
+    fn eighteen_decimal_amounts_round_trip() {
+        let base = to_base_units(&amount("1.000000000000000001"), 18).unwrap();
+        assert_eq!(base, U256::from(1_000_000_000_000_000_001u128));
+        assert_eq!(from_base_units(base, 18), amount("1.000000000000000001"));
+
+        let base = to_base_units(&amount("250"), 18).unwrap();
+        assert_eq!(
+            base,
+            U256::from(250u64) * U256::from(10u64).pow(U256::from(18u64))
+        );
+        assert_eq!(from_base_units(base, 18), amount("250"));
+    }
+
+    #[test]
+    fn trailing_zeros_do_not_count_as_decimals() {
+        let base = to_base_units(&amount("1.5000000"), 6).unwrap();
+        assert_eq!(base, U256::from(1_500_000u64));
     }
 
-    Ok(())
+    #[test]
+    fn rejects_more_decimals_than_the_token_has() {
+        assert!(matches!(
+            to_base_units(&amount("0.0000001"), 6),
+            Err(StabuseError::InvalidData(_))
+        ));
+        assert!(matches!(
+            to_base_units(&amount("1.0000000000000000001"), 18),
+            Err(StabuseError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_zero_and_negative_amounts() {
+        for value in ["0", "0.000", "-1", "-0.000001"] {
+            assert!(matches!(
+                to_base_units(&amount(value), 6),
+                Err(StabuseError::InvalidData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn accepts_up_to_u256_max() {
+        let max = U256::MAX.to_string();
+        assert_eq!(to_base_units(&amount(&max), 0).unwrap(), U256::MAX);
+        assert_eq!(from_base_units(U256::MAX, 0), amount(&max));
+
+        let too_large = amount(&max) + BigDecimal::from(1);
+        assert!(matches!(
+            to_base_units(&too_large, 0),
+            Err(StabuseError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_amounts_that_overflow_once_scaled() {
+        let max = U256::MAX.to_string();
+        assert!(matches!(
+            to_base_units(&amount(&max), 6),
+            Err(StabuseError::InvalidData(_))
+        ));
+    }
 }
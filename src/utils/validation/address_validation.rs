@@ -1,11 +1,48 @@
-use crate::error::StabuseError;
+use alloy::primitives::Address;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
 
-pub fn validate_address(address: &str) -> Result<(), StabuseError> {
-    if address.len() != 42 || !address.starts_with("0x") {
+use crate::{error::StabuseError, types::types::ChainFamily};
+
+/// Checks an address against the format of the network it is used on.
+pub fn validate_address(address: &str, chain_family: ChainFamily) -> Result<(), StabuseError> {
+    let valid = match chain_family {
+        ChainFamily::Evm => is_valid_evm_address(address),
+        ChainFamily::Solana => Pubkey::from_str(address).is_ok(),
+    };
+
+    if !valid {
         return Err(StabuseError::InvalidData(format!(
-            "Invalid address format for Network {}",
-            address
+            "Invalid address {}: expected {}",
+            address,
+            expected_address_format(chain_family)
         )));
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn expected_address_format(chain_family: ChainFamily) -> &'static str {
+    match chain_family {
+        ChainFamily::Evm => "a 0x-prefixed 20-byte hex address with a valid EIP-55 checksum",
+        ChainFamily::Solana => "a base58-encoded 32-byte public key",
+    }
+}
+
+/// All-lowercase and all-uppercase addresses carry no checksum under EIP-55;
+/// mixed-case addresses must match theirs.
+fn is_valid_evm_address(address: &str) -> bool {
+    let Some(hex) = address.strip_prefix("0x") else {
+        return false;
+    };
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Address::parse_checksummed(address, None).is_ok();
+    }
+
+    true
+}
@@ -1,19 +1,29 @@
 use sqlx::PgPool;
 use std::collections::HashMap;
 
-use crate::{error::StabuseError, network::network::is_asset_supported_on_network};
+use crate::{
+    error::StabuseError,
+    network::network::is_asset_supported_on_network,
+    types::types::{ChainFamily, ConfirmationPolicy, RpcEndpoint},
+    utils::validation::address_validation::{expected_address_format, validate_address},
+};
 
-pub fn validate_assets(assets: &HashMap<String, String>) -> Result<(), StabuseError> {
+pub fn validate_assets(
+    assets: &HashMap<String, String>,
+    chain_family: ChainFamily,
+) -> Result<(), StabuseError> {
     for (ticker, address) in assets {
         if ticker == "" {
             return Err(StabuseError::InvalidAssetFormat(format!(
                 "Ticker cannot be empty"
             )));
         }
-        if address.len() != 42 || !address.starts_with("0x") {
+        if validate_address(address, chain_family).is_err() {
             return Err(StabuseError::InvalidAssetFormat(format!(
-                "Invalid address format for asset {}: {}",
-                ticker, address
+                "Invalid address for asset {}: {}, expected {}",
+                ticker,
+                address,
+                expected_address_format(chain_family)
             )));
         }
     }
@@ -79,3 +89,75 @@ pub async fn validate_supported_assets(
 
     Ok(())
 }
+
+pub fn validate_confirmation_policy(
+    network_name: &str,
+    chain_family: ChainFamily,
+    policy: &ConfirmationPolicy,
+) -> Result<(), StabuseError> {
+    let is_solana = chain_family == ChainFamily::Solana;
+
+    match policy {
+        ConfirmationPolicy::Depth { blocks } if is_solana => {
+            Err(StabuseError::InvalidData(format!(
+                "Network {} uses commitment levels, not a block depth ({} blocks requested)",
+                network_name, blocks
+            )))
+        }
+        ConfirmationPolicy::Depth { blocks: 0 } => Err(StabuseError::InvalidData(
+            "Confirmation depth must be at least 1 block".to_string(),
+        )),
+        ConfirmationPolicy::Confirmed if !is_solana => Err(StabuseError::InvalidData(format!(
+            "Network {} does not support the confirmed commitment level",
+            network_name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_rpc_endpoints(
+    rpc_endpoints: &[RpcEndpoint],
+    rpc_quorum: u32,
+) -> Result<(), StabuseError> {
+    if rpc_endpoints.is_empty() {
+        return Err(StabuseError::InvalidData(
+            "At least one RPC endpoint is required".to_string(),
+        ));
+    }
+
+    for (i, endpoint) in rpc_endpoints.iter().enumerate() {
+        match reqwest::Url::parse(&endpoint.url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            _ => {
+                return Err(StabuseError::InvalidData(format!(
+                    "Invalid RPC endpoint URL: {}",
+                    endpoint.url
+                )))
+            }
+        }
+        if endpoint.weight == 0 {
+            return Err(StabuseError::InvalidData(format!(
+                "RPC endpoint {} must have a weight of at least 1",
+                endpoint.url
+            )));
+        }
+        if rpc_endpoints[..i]
+            .iter()
+            .any(|other| other.url == endpoint.url)
+        {
+            return Err(StabuseError::InvalidData(format!(
+                "Duplicate RPC endpoint: {}",
+                endpoint.url
+            )));
+        }
+    }
+
+    if rpc_quorum == 0 || rpc_quorum as usize > rpc_endpoints.len() {
+        return Err(StabuseError::InvalidData(format!(
+            "RPC quorum must be between 1 and {}",
+            rpc_endpoints.len()
+        )));
+    }
+
+    Ok(())
+}
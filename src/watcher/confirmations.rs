@@ -0,0 +1,195 @@
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::error as TracingError;
+
+use crate::{
+    core::{
+        adapter::adapter::chain_adapter,
+        rpc::rpc::{quorum_reached, with_failover},
+    },
+    db::migrations::payments::select_queries::GET_PENDING_PAYMENTS_AWAITING_CONFIRMATION,
+    error::StabuseError,
+    network::network::get_network,
+    payment::payment::{
+        complete_payment, get_payment_transfers, mark_payment_confirming, mark_payment_dropped,
+        mark_payment_reorged, mark_payment_seen, move_payment_transfer,
+    },
+    types::types::{AwaitingConfirmation, ConfirmationCheck, NetworkDB, PaymentTransfer},
+};
+
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Walks every payment that has been seen on-chain but not yet confirmed,
+/// re-checking its block hash and confirmation depth on each pass.
+pub async fn start_confirmation_tracker(pool: PgPool) {
+    loop {
+        match sqlx::query_as::<_, AwaitingConfirmation>(GET_PENDING_PAYMENTS_AWAITING_CONFIRMATION)
+            .fetch_all(&pool)
+            .await
+        {
+            Ok(payments) => {
+                for payment in payments {
+                    if let Err(err) = track_confirmation(&pool, &payment).await {
+                        TracingError!(
+                            error = ?err,
+                            pending_payment_id = payment.pending_payment.id,
+                            "Error tracking payment confirmations"
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                TracingError!(error = ?err, "Error loading payments awaiting confirmation");
+            }
+        }
+
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+    }
+}
+
+/// Checks every transfer counted towards the payment, so an earlier
+/// transfer of a split payment that is reorged or dropped is caught too. The
+/// payment settles once its shallowest transfer is deep enough.
+async fn track_confirmation(
+    pool: &PgPool,
+    payment: &AwaitingConfirmation,
+) -> Result<(), StabuseError> {
+    let pending_payment = &payment.pending_payment;
+    let (Some(tx_hash), Some(block_number), Some(block_hash)) = (
+        pending_payment.tx_hash.as_deref(),
+        pending_payment.block_number,
+        pending_payment.block_hash.as_deref(),
+    ) else {
+        return Err(StabuseError::Internal(format!(
+            "Pending payment {} is missing its inclusion details",
+            pending_payment.id
+        )));
+    };
+
+    let mut transfers = get_payment_transfers(pool, pending_payment.id).await?;
+    if !transfers.iter().any(|transfer| transfer.tx_hash == tx_hash) {
+        transfers.push(PaymentTransfer {
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            block_hash: block_hash.to_string(),
+        });
+    }
+
+    let network = get_network(pool, payment.chain_id).await?;
+    let mut shallowest: Option<u64> = None;
+    let mut satisfied = true;
+
+    for transfer in &transfers {
+        let Some(check) = check_transfer(&network, transfer).await? else {
+            return Ok(());
+        };
+
+        match check {
+            ConfirmationCheck::Confirmations {
+                confirmations,
+                satisfied: transfer_satisfied,
+            } => {
+                shallowest = Some(shallowest.map_or(confirmations, |c| c.min(confirmations)));
+                satisfied &= transfer_satisfied;
+            }
+            ConfirmationCheck::Moved(inclusion) if transfer.tx_hash == tx_hash => {
+                mark_payment_reorged(pool, pending_payment).await?;
+                mark_payment_seen(pool, pending_payment, tx_hash, &inclusion).await?;
+                return Ok(());
+            }
+            ConfirmationCheck::Moved(inclusion) => {
+                move_payment_transfer(pool, pending_payment, &transfer.tx_hash, &inclusion).await?;
+                return Ok(());
+            }
+            ConfirmationCheck::Dropped => {
+                mark_payment_dropped(pool, pending_payment, &transfer.tx_hash).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let confirmations = shallowest.unwrap_or_default();
+    if satisfied {
+        complete_payment(pool, pending_payment, confirmations).await?;
+    } else {
+        mark_payment_confirming(pool, pending_payment, confirmations).await?;
+    }
+
+    Ok(())
+}
+
+/// Checks one transfer's inclusion and depth. Returns `None` when the
+/// endpoints disagree on a reorg or a drop, so it is re-checked next pass.
+async fn check_transfer(
+    network: &NetworkDB,
+    transfer: &PaymentTransfer,
+) -> Result<Option<ConfirmationCheck>, StabuseError> {
+    let adapter = chain_adapter(network.chain_family);
+    let policy = &network.confirmation_policy;
+    let tx_hash = transfer.tx_hash.as_str();
+    let block_number = transfer.block_number as u64;
+    let block_hash = transfer.block_hash.as_str();
+
+    let check = with_failover(network, |rpc_url| async move {
+        adapter
+            .check_confirmations(&rpc_url, tx_hash, block_number, block_hash, policy)
+            .await
+    })
+    .await?;
+
+    // With a quorum configured, a depth reached on one endpoint is only
+    // trusted once enough of the other endpoints report the same.
+    let check = match check {
+        ConfirmationCheck::Confirmations {
+            confirmations,
+            satisfied: true,
+        } if network.rpc_quorum > 1 => ConfirmationCheck::Confirmations {
+            confirmations,
+            satisfied: quorum_reached(network, |rpc_url| async move {
+                let check = adapter
+                    .check_confirmations(&rpc_url, tx_hash, block_number, block_hash, policy)
+                    .await?;
+                Ok(matches!(
+                    check,
+                    ConfirmationCheck::Confirmations {
+                        satisfied: true,
+                        ..
+                    }
+                ))
+            })
+            .await,
+        },
+        check => check,
+    };
+
+    // A reorg or a dropped transaction throws the inclusion away, so a
+    // single endpoint lagging behind must not be enough: the verdict is
+    // re-checked and only acted on once a quorum reports the same.
+    if let ConfirmationCheck::Moved(_) | ConfirmationCheck::Dropped = &check {
+        let verdict = &check;
+        let agreed = quorum_reached(network, |rpc_url| async move {
+            let recheck = adapter
+                .check_confirmations(&rpc_url, tx_hash, block_number, block_hash, policy)
+                .await?;
+            Ok(match (verdict, recheck) {
+                (ConfirmationCheck::Moved(seen), ConfirmationCheck::Moved(inclusion)) => {
+                    seen.block_hash == inclusion.block_hash
+                }
+                (ConfirmationCheck::Dropped, ConfirmationCheck::Dropped) => true,
+                _ => false,
+            })
+        })
+        .await;
+
+        if !agreed {
+            tracing::warn!(
+                "Endpoints disagree on {:?} for transfer {}; re-checking next pass",
+                check,
+                tx_hash
+            );
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(check))
+}
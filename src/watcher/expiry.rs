@@ -0,0 +1,23 @@
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::error as TracingError;
+
+use crate::payment::payment::expire_pending_payments;
+
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically expires pending payments that were abandoned before any
+/// matching transfer was seen on-chain.
+pub async fn start_expiry_sweeper(pool: PgPool) {
+    loop {
+        match expire_pending_payments(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Expired {} pending payments", count),
+            Err(err) => {
+                TracingError!(error = ?err, "Error expiring pending payments");
+            }
+        }
+
+        tokio::time::sleep(EXPIRY_SWEEP_INTERVAL).await;
+    }
+}
@@ -0,0 +1,3 @@
+pub mod confirmations;
+pub mod expiry;
+pub mod watcher;
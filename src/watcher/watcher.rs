@@ -0,0 +1,410 @@
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{Filter, Log},
+};
+use alloy_sol_types::SolEvent;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcBlockConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::instruction::TokenInstruction;
+use sqlx::PgPool;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::error as TracingError;
+
+use crate::{
+    core::{
+        evm::evm::{decode_transfer_log, IERC20},
+        rpc::rpc::with_failover,
+        sol::sol::{is_skipped_slot, transaction_account_keys},
+    },
+    db::migrations::{
+        networks::{
+            insert_and_update_networks::UPSERT_WATCHER_CHECKPOINT,
+            select_queries::GET_WATCHER_CHECKPOINT,
+        },
+        payments::select_queries::GET_PENDING_PAYMENTS_FOR_NETWORK,
+    },
+    error::StabuseError,
+    network::network::{get_all_networks, get_network},
+    queue::queue::VerificationQueue,
+    types::types::{ChainFamily, NetworkDB, PendingPaymentMatch, TransactionVerificationMessage},
+};
+
+const NETWORK_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_EVM_BLOCK_RANGE: u64 = 500;
+const MAX_SOLANA_SLOT_RANGE: u64 = 50;
+
+/// Spawns a watcher for every network in the `networks` table and keeps
+/// picking up networks that are added while the server is running.
+pub async fn start_watchers(pool: PgPool, queue: Arc<dyn VerificationQueue>) {
+    let mut running: HashSet<i64> = HashSet::new();
+
+    loop {
+        match get_all_networks(&pool).await {
+            Ok(networks) => {
+                for network in networks {
+                    if !running.insert(network.chain_id) {
+                        continue;
+                    }
+
+                    tracing::info!(
+                        "Starting chain watcher for {} ({})",
+                        network.name,
+                        network.chain_id
+                    );
+                    let pool = pool.clone();
+                    let queue = queue.clone();
+                    tokio::spawn(async move {
+                        watch_network(pool, network.chain_id, queue).await;
+                    });
+                }
+            }
+            Err(err) => {
+                TracingError!(error = ?err, "Error loading networks for chain watchers");
+            }
+        }
+
+        tokio::time::sleep(NETWORK_REFRESH_INTERVAL).await;
+    }
+}
+
+/// Reloads the network on every tick, so assets, endpoints, quorum and
+/// confirmation policy changed by an admin apply without a restart.
+async fn watch_network(pool: PgPool, chain_id: i64, queue: Arc<dyn VerificationQueue>) {
+    loop {
+        let network = match get_network(&pool, chain_id).await {
+            Ok(network) => network,
+            Err(err) => {
+                TracingError!(error = ?err, chain_id, "Error reloading network for chain watcher");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+
+        let (pool, network_ref) = (&pool, &network);
+        let queue = queue.as_ref();
+        let result = with_failover(&network, |rpc_url| async move {
+            match network_ref.chain_family {
+                ChainFamily::Evm => scan_evm_blocks(pool, network_ref, &rpc_url, queue).await,
+                ChainFamily::Solana => scan_solana_slots(pool, network_ref, &rpc_url, queue).await,
+            }
+        })
+        .await;
+
+        if let Err(err) = result {
+            TracingError!(error = ?err, network = %network.name, "Chain watcher error");
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn scan_evm_blocks(
+    pool: &PgPool,
+    network: &NetworkDB,
+    rpc_url: &str,
+    queue: &dyn VerificationQueue,
+) -> Result<(), StabuseError> {
+    let rpc = rpc_url
+        .parse()
+        .map_err(|e| StabuseError::Rpc(format!("Invalid RPC URL: {}", e)))?;
+    let provider = ProviderBuilder::new().on_http(rpc);
+    let head = provider.get_block_number().await?;
+
+    let from_block = match get_checkpoint(pool, network.chain_id).await? {
+        Some(last_block) => last_block + 1,
+        None => head,
+    };
+    if from_block > head {
+        return Ok(());
+    }
+    let to_block = head.min(from_block + MAX_EVM_BLOCK_RANGE - 1);
+
+    let tokens: HashMap<Address, String> = network
+        .supported_assets
+        .iter()
+        .filter(|asset| asset.enabled)
+        .filter_map(|asset| {
+            Address::from_str(&asset.address)
+                .ok()
+                .map(|address| (address, asset.ticker.clone()))
+        })
+        .collect();
+
+    if !tokens.is_empty() {
+        let filter = Filter::new()
+            .address(tokens.keys().cloned().collect::<Vec<Address>>())
+            .event_signature(IERC20::Transfer::SIGNATURE_HASH)
+            .from_block(from_block)
+            .to_block(to_block);
+        let logs = provider.get_logs(&filter).await?;
+
+        if !logs.is_empty() {
+            let pending_payments = get_open_pending_payments(pool, network).await?;
+            for log in logs {
+                if let Some((tx_hash, ids)) = match_evm_transfer(&log, &tokens, &pending_payments) {
+                    for pending_payment_id in ids {
+                        enqueue_verification(network, pending_payment_id, &tx_hash, queue).await;
+                    }
+                }
+            }
+        }
+    }
+
+    set_checkpoint(pool, network.chain_id, to_block).await
+}
+
+fn match_evm_transfer(
+    log: &Log,
+    tokens: &HashMap<Address, String>,
+    pending_payments: &[PendingPaymentMatch],
+) -> Option<(String, Vec<i32>)> {
+    let ticker = tokens.get(&log.inner.address)?;
+    let transfer = decode_transfer_log(log)?;
+    let tx_hash = log.transaction_hash?;
+
+    let ids: Vec<i32> = pending_payments
+        .iter()
+        .filter(|payment| {
+            payment.asset == *ticker
+                && Address::from_str(&payment.sender).ok() == Some(transfer.from)
+                && payment
+                    .merchant_address
+                    .as_deref()
+                    .and_then(|address| Address::from_str(address).ok())
+                    == Some(transfer.to)
+        })
+        .map(|payment| payment.id)
+        .collect();
+
+    if ids.is_empty() {
+        return None;
+    }
+
+    Some((tx_hash.to_string(), ids))
+}
+
+async fn scan_solana_slots(
+    pool: &PgPool,
+    network: &NetworkDB,
+    rpc_url: &str,
+    queue: &dyn VerificationQueue,
+) -> Result<(), StabuseError> {
+    let rpc_client =
+        RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let head = rpc_client
+        .get_slot()
+        .await
+        .map_err(|e| StabuseError::Rpc(format!("Failed to get current slot: {}", e)))?;
+
+    let from_slot = match get_checkpoint(pool, network.chain_id).await? {
+        Some(last_slot) => last_slot + 1,
+        None => head,
+    };
+    if from_slot > head {
+        return Ok(());
+    }
+    let to_slot = head.min(from_slot + MAX_SOLANA_SLOT_RANGE - 1);
+
+    let mints: HashMap<Pubkey, String> = network
+        .supported_assets
+        .iter()
+        .filter(|asset| asset.enabled)
+        .filter_map(|asset| {
+            Pubkey::from_str(&asset.address)
+                .ok()
+                .map(|mint| (mint, asset.ticker.clone()))
+        })
+        .collect();
+
+    let pending_payments = get_open_pending_payments(pool, network).await?;
+    if mints.is_empty() || pending_payments.is_empty() {
+        return set_checkpoint(pool, network.chain_id, to_slot).await;
+    }
+
+    for slot in from_slot..=to_slot {
+        let block = match rpc_client
+            .get_block_with_config(
+                slot,
+                RpcBlockConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    transaction_details: Some(TransactionDetails::Full),
+                    rewards: Some(false),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+        {
+            Ok(block) => block,
+            Err(e) if is_skipped_slot(&e) => {
+                tracing::debug!("Slot {} was skipped", slot);
+                continue;
+            }
+            Err(e) => {
+                // Keep the slots scanned so far; this one is retried from
+                // the checkpoint, on the next endpoint or the next tick.
+                if slot > from_slot {
+                    set_checkpoint(pool, network.chain_id, slot - 1).await?;
+                }
+                return Err(StabuseError::Rpc(format!(
+                    "Failed to fetch block for slot {}: {}",
+                    slot, e
+                )));
+            }
+        };
+
+        for encoded in block.transactions.unwrap_or_default() {
+            if let Some(meta) = &encoded.meta {
+                if meta.status.is_err() {
+                    continue;
+                }
+            }
+            let Some(transaction) = encoded.transaction.decode() else {
+                continue;
+            };
+            let Some(signature) = transaction.signatures.first() else {
+                continue;
+            };
+
+            let account_keys =
+                transaction_account_keys(&transaction.message, encoded.meta.as_ref());
+            let ids = match_solana_transfers(
+                &account_keys,
+                transaction.message.instructions(),
+                &mints,
+                &pending_payments,
+            );
+            for pending_payment_id in ids {
+                enqueue_verification(network, pending_payment_id, &signature.to_string(), queue)
+                    .await;
+            }
+        }
+    }
+
+    set_checkpoint(pool, network.chain_id, to_slot).await
+}
+
+fn match_solana_transfers(
+    account_keys: &[Pubkey],
+    instructions: &[solana_sdk::instruction::CompiledInstruction],
+    mints: &HashMap<Pubkey, String>,
+    pending_payments: &[PendingPaymentMatch],
+) -> Vec<i32> {
+    let mut ids = vec![];
+
+    for instruction in instructions {
+        if account_keys.get(instruction.program_id_index as usize) != Some(&spl_token::id()) {
+            continue;
+        }
+        if !matches!(
+            TokenInstruction::unpack(&instruction.data),
+            Ok(TokenInstruction::TransferChecked { .. })
+        ) {
+            continue;
+        }
+
+        // TransferChecked accounts: [source, mint, destination, authority]
+        let accounts: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .filter_map(|index| account_keys.get(*index as usize).cloned())
+            .collect();
+        if accounts.len() < 4 {
+            continue;
+        }
+        let Some(ticker) = mints.get(&accounts[1]) else {
+            continue;
+        };
+
+        for payment in pending_payments {
+            if payment.asset != *ticker || ids.contains(&payment.id) {
+                continue;
+            }
+            let Ok(payer) = Pubkey::from_str(&payment.sender) else {
+                continue;
+            };
+            let Some(merchant) = payment
+                .merchant_address
+                .as_deref()
+                .and_then(|address| Pubkey::from_str(address).ok())
+            else {
+                continue;
+            };
+
+            if accounts[3] == payer
+                && accounts[2] == get_associated_token_address(&merchant, &accounts[1])
+            {
+                ids.push(payment.id);
+            }
+        }
+    }
+
+    ids
+}
+
+async fn enqueue_verification(
+    network: &NetworkDB,
+    pending_payment_id: i32,
+    tx_hash: &str,
+    queue: &dyn VerificationQueue,
+) {
+    tracing::info!(
+        "Watcher matched tx {} to pending payment {} on {}",
+        tx_hash,
+        pending_payment_id,
+        network.name
+    );
+
+    let message = TransactionVerificationMessage {
+        pending_payment_id,
+        tx_hash: tx_hash.to_string(),
+        chain_id: network.chain_id,
+        network: network.name.clone(),
+    };
+
+    if let Err(e) = queue.publish(&message).await {
+        TracingError!(error = ?e, "Failed to publish watcher verification message");
+    }
+}
+
+async fn get_open_pending_payments(
+    pool: &PgPool,
+    network: &NetworkDB,
+) -> Result<Vec<PendingPaymentMatch>, StabuseError> {
+    let pending_payments =
+        sqlx::query_as::<_, PendingPaymentMatch>(GET_PENDING_PAYMENTS_FOR_NETWORK)
+            .bind(&network.name)
+            .bind(network.chain_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(pending_payments)
+}
+
+async fn get_checkpoint(pool: &PgPool, chain_id: i64) -> Result<Option<u64>, StabuseError> {
+    let last_block: Option<i64> = sqlx::query_scalar(GET_WATCHER_CHECKPOINT)
+        .bind(chain_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(last_block.map(|block| block as u64))
+}
+
+async fn set_checkpoint(pool: &PgPool, chain_id: i64, last_block: u64) -> Result<(), StabuseError> {
+    sqlx::query(UPSERT_WATCHER_CHECKPOINT)
+        .bind(chain_id)
+        .bind(last_block as i64)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use futures::future::join_all;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use tracing::error as TracingError;
+
+use crate::{
+    db::migrations::webhooks::inserts_and_updates::{
+        ADD_WEBHOOK_DELIVERY_ATTEMPT, CLAIM_DUE_WEBHOOK_DELIVERIES, UPDATE_WEBHOOK_DELIVERY_RESULT,
+    },
+    error::StabuseError,
+    merchant::merchant::get_merchant_webhook_secrets,
+    types::types::{DueWebhookDelivery, WebhookDeliveryStatus},
+    utils::utils::send_webhook_notification,
+};
+
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DELIVERY_BATCH_SIZE: i64 = 20;
+/// How long a claimed delivery is hidden from other workers. Comfortably
+/// longer than the webhook request timeout.
+const DELIVERY_LEASE: Duration = Duration::from_secs(60);
+
+/// Deliveries are given up on after this many failed attempts.
+const MAX_WEBHOOK_ATTEMPTS: i32 = 8;
+/// Delay before the first retry; each further retry waits twice as long.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+const MAX_RESPONSE_BODY_CHARS: usize = 512;
+
+/// Sends due webhook deliveries from the outbox for the lifetime of the
+/// server. Failed deliveries are retried with exponential backoff until
+/// `MAX_WEBHOOK_ATTEMPTS`; every attempt is recorded.
+pub async fn start_webhook_delivery_worker(pool: PgPool) {
+    loop {
+        match deliver_due_webhooks(&pool).await {
+            Ok(0) => {}
+            // More may be due right away.
+            Ok(_) => continue,
+            Err(err) => {
+                TracingError!(error = ?err, "Error delivering webhooks");
+            }
+        }
+
+        tokio::time::sleep(DELIVERY_POLL_INTERVAL).await;
+    }
+}
+
+async fn deliver_due_webhooks(pool: &PgPool) -> Result<usize, StabuseError> {
+    let deliveries = sqlx::query_as::<_, DueWebhookDelivery>(CLAIM_DUE_WEBHOOK_DELIVERIES)
+        .bind(DELIVERY_BATCH_SIZE)
+        .bind(DELIVERY_LEASE.as_secs_f64())
+        .fetch_all(pool)
+        .await?;
+
+    let count = deliveries.len();
+    let results = join_all(
+        deliveries
+            .iter()
+            .map(|delivery| attempt_delivery(pool, delivery)),
+    )
+    .await;
+
+    for (delivery, result) in deliveries.iter().zip(results) {
+        if let Err(err) = result {
+            TracingError!(
+                error = ?err,
+                webhook_delivery_id = delivery.id,
+                "Error recording webhook delivery attempt"
+            );
+        }
+    }
+
+    Ok(count)
+}
+
+async fn attempt_delivery(
+    pool: &PgPool,
+    delivery: &DueWebhookDelivery,
+) -> Result<(), StabuseError> {
+    let secrets = get_merchant_webhook_secrets(pool, delivery.merchant_id).await?;
+
+    let started = Instant::now();
+    let result =
+        send_webhook_notification(&delivery.url, &delivery.payload, &secrets.signing_secrets())
+            .await;
+    let latency_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (status_code, response_body, error) = match result {
+        Ok((status, body)) => (
+            Some(status as i32),
+            Some(
+                body.chars()
+                    .take(MAX_RESPONSE_BODY_CHARS)
+                    .collect::<String>(),
+            ),
+            None,
+        ),
+        Err(e) => (None, None, Some(e.to_string())),
+    };
+    let succeeded = status_code.is_some_and(|status| (200..300).contains(&status));
+
+    let attempts = delivery.attempts + 1;
+    let (status, retry_in) = if succeeded {
+        (WebhookDeliveryStatus::Succeeded, Duration::ZERO)
+    } else if attempts >= MAX_WEBHOOK_ATTEMPTS {
+        (WebhookDeliveryStatus::Failed, Duration::ZERO)
+    } else {
+        (
+            WebhookDeliveryStatus::Pending,
+            RETRY_BASE_DELAY * 2u32.pow(attempts as u32 - 1),
+        )
+    };
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(ADD_WEBHOOK_DELIVERY_ATTEMPT)
+        .bind(delivery.id)
+        .bind(attempts)
+        .bind(status_code)
+        .bind(latency_ms)
+        .bind(&response_body)
+        .bind(&error)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(UPDATE_WEBHOOK_DELIVERY_RESULT)
+        .bind(delivery.id)
+        .bind(status)
+        .bind(attempts)
+        .bind(status_code)
+        .bind(retry_in.as_secs_f64())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    match status {
+        WebhookDeliveryStatus::Succeeded => tracing::info!(
+            "Webhook delivery {} succeeded with status {:?} in {}ms",
+            delivery.id,
+            status_code,
+            latency_ms
+        ),
+        WebhookDeliveryStatus::Pending => tracing::warn!(
+            "Webhook delivery {} attempt {} failed ({:?} {:?}), retrying in {}s",
+            delivery.id,
+            attempts,
+            status_code,
+            error,
+            retry_in.as_secs()
+        ),
+        WebhookDeliveryStatus::Failed => tracing::warn!(
+            "Webhook delivery {} failed after {} attempts ({:?} {:?})",
+            delivery.id,
+            attempts,
+            status_code,
+            error
+        ),
+    }
+
+    Ok(())
+}
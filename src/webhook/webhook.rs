@@ -0,0 +1,226 @@
+use reqwest::Url;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    db::migrations::webhooks::{
+        inserts_and_updates::{
+            ADD_WEBHOOK_ENDPOINT, ADD_WEBHOOK_EVENT, REDELIVER_WEBHOOK, REMOVE_WEBHOOK_ENDPOINT,
+            UPDATE_WEBHOOK_ENDPOINT,
+        },
+        select_queries::{
+            COUNT_MERCHANT_WEBHOOK_ENDPOINTS, GET_MERCHANT_WEBHOOK_DELIVERIES,
+            GET_MERCHANT_WEBHOOK_DELIVERY, GET_MERCHANT_WEBHOOK_ENDPOINTS,
+            GET_WEBHOOK_DELIVERY_ATTEMPTS,
+        },
+    },
+    error::StabuseError,
+    types::types::{
+        AccountMode, PaymentStatus, UpdateWebhookEndpointRequest, WebhookDelivery,
+        WebhookDeliveryAttempt, WebhookDeliveryDetails, WebhookDeliveryQuery, WebhookEndpoint,
+    },
+};
+
+const MAX_WEBHOOK_ENDPOINTS_PER_MERCHANT: i64 = 16;
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+
+fn validate_webhook_url(url: &str) -> Result<(), StabuseError> {
+    let parsed = Url::parse(url)
+        .map_err(|e| StabuseError::InvalidData(format!("Invalid webhook URL: {}", e)))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(StabuseError::InvalidData(
+            "Webhook URL must be an http or https URL".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Event types are `payment.<status>` for any payment status.
+fn validate_event_types(event_types: &[String]) -> Result<(), StabuseError> {
+    for event_type in event_types {
+        let known = event_type.strip_prefix("payment.").is_some_and(|status| {
+            PaymentStatus::ALL
+                .iter()
+                .any(|known| known.as_str() == status)
+        });
+        if !known {
+            return Err(StabuseError::InvalidData(format!(
+                "Unknown webhook event type: {}",
+                event_type
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn add_webhook_endpoint(
+    pool: &PgPool,
+    merchant_id: i32,
+    url: &str,
+    event_types: Vec<String>,
+    mode: AccountMode,
+) -> Result<WebhookEndpoint, StabuseError> {
+    validate_webhook_url(url)?;
+    validate_event_types(&event_types)?;
+
+    let count: i64 = sqlx::query_scalar(COUNT_MERCHANT_WEBHOOK_ENDPOINTS)
+        .bind(merchant_id)
+        .fetch_one(pool)
+        .await?;
+    if count >= MAX_WEBHOOK_ENDPOINTS_PER_MERCHANT {
+        return Err(StabuseError::InvalidData(format!(
+            "A merchant can register at most {} webhook endpoints",
+            MAX_WEBHOOK_ENDPOINTS_PER_MERCHANT
+        )));
+    }
+
+    let endpoint = sqlx::query_as::<_, WebhookEndpoint>(ADD_WEBHOOK_ENDPOINT)
+        .bind(merchant_id)
+        .bind(url)
+        .bind(event_types)
+        .bind(mode)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(endpoint)
+}
+
+pub async fn update_webhook_endpoint(
+    pool: &PgPool,
+    merchant_id: i32,
+    update: &UpdateWebhookEndpointRequest,
+) -> Result<WebhookEndpoint, StabuseError> {
+    if let Some(url) = &update.url {
+        validate_webhook_url(url)?;
+    }
+    if let Some(event_types) = &update.event_types {
+        validate_event_types(event_types)?;
+    }
+
+    sqlx::query_as::<_, WebhookEndpoint>(UPDATE_WEBHOOK_ENDPOINT)
+        .bind(update.id)
+        .bind(merchant_id)
+        .bind(&update.url)
+        .bind(&update.event_types)
+        .bind(update.enabled)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            StabuseError::InvalidData(format!("Webhook endpoint {} not found", update.id))
+        })
+}
+
+pub async fn remove_webhook_endpoint(
+    pool: &PgPool,
+    merchant_id: i32,
+    endpoint_id: i32,
+) -> Result<i32, StabuseError> {
+    sqlx::query_scalar(REMOVE_WEBHOOK_ENDPOINT)
+        .bind(endpoint_id)
+        .bind(merchant_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            StabuseError::InvalidData(format!("Webhook endpoint {} not found", endpoint_id))
+        })
+}
+
+pub async fn get_webhook_endpoints(
+    pool: &PgPool,
+    merchant_id: i32,
+) -> Result<Vec<WebhookEndpoint>, StabuseError> {
+    let endpoints = sqlx::query_as::<_, WebhookEndpoint>(GET_MERCHANT_WEBHOOK_ENDPOINTS)
+        .bind(merchant_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(endpoints)
+}
+
+/// Writes an event to the webhook outbox inside the caller's transaction,
+/// with one delivery per endpoint of the same mode subscribed to it. The
+/// delivery worker sends them once the transaction commits; if it rolls
+/// back, nothing is sent.
+pub async fn enqueue_webhook_event(
+    tx: &mut Transaction<'_, Postgres>,
+    merchant_id: i32,
+    mode: AccountMode,
+    event: &str,
+    payload_json: &str,
+) -> Result<(), StabuseError> {
+    sqlx::query(ADD_WEBHOOK_EVENT)
+        .bind(merchant_id)
+        .bind(event)
+        .bind(payload_json)
+        .bind(mode)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_webhook_deliveries(
+    pool: &PgPool,
+    merchant_id: i32,
+    query: &WebhookDeliveryQuery,
+) -> Result<Vec<WebhookDelivery>, StabuseError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(GET_MERCHANT_WEBHOOK_DELIVERIES)
+        .bind(merchant_id)
+        .bind(query.endpoint_id)
+        .bind(query.status)
+        .bind(limit)
+        .bind(query.offset.unwrap_or(0).max(0))
+        .bind(query.mode)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(deliveries)
+}
+
+/// A delivery with its payload and every attempt made so far.
+pub async fn get_webhook_delivery(
+    pool: &PgPool,
+    merchant_id: i32,
+    delivery_id: i32,
+) -> Result<(WebhookDeliveryDetails, Vec<WebhookDeliveryAttempt>), StabuseError> {
+    let delivery = sqlx::query_as::<_, WebhookDeliveryDetails>(GET_MERCHANT_WEBHOOK_DELIVERY)
+        .bind(delivery_id)
+        .bind(merchant_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            StabuseError::InvalidData(format!("Webhook delivery {} not found", delivery_id))
+        })?;
+
+    let attempts = sqlx::query_as::<_, WebhookDeliveryAttempt>(GET_WEBHOOK_DELIVERY_ATTEMPTS)
+        .bind(delivery_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok((delivery, attempts))
+}
+
+/// Queues a delivery for an immediate attempt, whatever its status. A
+/// delivery that already used up its retries gets one more attempt.
+pub async fn redeliver_webhook(
+    pool: &PgPool,
+    merchant_id: i32,
+    delivery_id: i32,
+) -> Result<i32, StabuseError> {
+    sqlx::query_scalar(REDELIVER_WEBHOOK)
+        .bind(delivery_id)
+        .bind(merchant_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            StabuseError::InvalidData(format!("Webhook delivery {} not found", delivery_id))
+        })
+}